@@ -0,0 +1,240 @@
+//! Animated GIF and APNG written one frame at a time.
+//!
+//! Only the last pushed frame is kept in memory: it is written once the next
+//! one arrives, or with the final hold when the animation is finished.
+
+use crate::png::{self, chunk};
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame, RgbImage};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+/// File format of an [`Animation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Gif,
+    /// Animated PNG, which keeps the colors exact.
+    Apng,
+}
+
+impl Format {
+    /// Format of a file named `path`: `.gif`, or `.png` and `.apng` for APNG.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "gif" => Ok(Format::Gif),
+            "png" | "apng" => Ok(Format::Apng),
+            _ => Err(format!(
+                "unknown animation format {}, expected gif, png or apng",
+                s
+            )),
+        }
+    }
+}
+
+/// Timing of an [`Animation`], in milliseconds.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Time every frame is shown.
+    pub delay: u16,
+    /// Time the last frame is shown before the animation loops.
+    pub hold: u16,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            delay: 200,
+            hold: 2000,
+        }
+    }
+}
+
+/// An endlessly looping animation of frames of the same size.
+pub struct Animation<'a, W: Write + Seek> {
+    encoder: Encoder<'a, W>,
+    size: (u32, u32),
+    options: Options,
+    pending: Option<RgbImage>,
+    frames: u32,
+}
+
+enum Encoder<'a, W: Write + Seek> {
+    Gif(GifEncoder<&'a mut W>),
+    Apng(Apng<'a, W>),
+}
+
+impl<'a, W: Write + Seek> Animation<'a, W> {
+    /// Start an animation of frames of `size` in `out`. Frames are written as
+    /// they are pushed, flush `out` once the animation is finished.
+    pub fn new(
+        out: &'a mut W,
+        format: Format,
+        size: (u32, u32),
+        options: Options,
+    ) -> io::Result<Animation<'a, W>> {
+        if size.0 == 0 || size.1 == 0 || (format == Format::Gif && size.0.max(size.1) > 0xffff) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("can not animate frames of {}x{}", size.0, size.1),
+            ));
+        }
+        let encoder = match format {
+            Format::Gif => {
+                let mut encoder = GifEncoder::new_with_speed(out, 10);
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(io::Error::other)?;
+                Encoder::Gif(encoder)
+            }
+            Format::Apng => Encoder::Apng(Apng::new(out, size)?),
+        };
+        Ok(Animation {
+            encoder,
+            size,
+            options,
+            pending: None,
+            frames: 0,
+        })
+    }
+    /// Frames pushed so far.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+    /// Add a frame, it must have the size the animation was started with.
+    pub fn push(&mut self, frame: RgbImage) -> io::Result<()> {
+        if frame.dimensions() != self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame of {}x{} in an animation of {}x{}",
+                    frame.width(),
+                    frame.height(),
+                    self.size.0,
+                    self.size.1
+                ),
+            ));
+        }
+        if let Some(previous) = self.pending.replace(frame) {
+            self.write(previous, self.options.delay)?;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+    /// Write the last frame with the final hold and end the animation.
+    pub fn finish(mut self) -> io::Result<()> {
+        let last = self.pending.take().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "animation has no frames")
+        })?;
+        self.write(last, self.options.hold)?;
+        match self.encoder {
+            // the trailer is written when the encoder is dropped
+            Encoder::Gif(_) => Ok(()),
+            Encoder::Apng(apng) => apng.finish(),
+        }
+    }
+    fn write(&mut self, frame: RgbImage, delay: u16) -> io::Result<()> {
+        match &mut self.encoder {
+            Encoder::Gif(encoder) => {
+                let frame = Frame::from_parts(
+                    DynamicImage::ImageRgb8(frame).to_rgba8(),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(delay as u32, 1),
+                );
+                encoder.encode_frame(frame).map_err(io::Error::other)
+            }
+            Encoder::Apng(apng) => apng.frame(&frame, delay),
+        }
+    }
+}
+
+// APNG written by hand, the frame count in acTL is patched in when finished
+struct Apng<'a, W: Write + Seek> {
+    out: &'a mut W,
+    size: (u32, u32),
+    // position of the acTL chunk
+    control: u64,
+    sequence: u32,
+    frames: u32,
+}
+
+impl<'a, W: Write + Seek> Apng<'a, W> {
+    fn new(out: &'a mut W, (width, height): (u32, u32)) -> io::Result<Apng<'a, W>> {
+        out.write_all(png::SIGNATURE)?;
+        let mut header = vec![];
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        // 8 bits RGB, deflate, adaptive filters, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        chunk(out, b"IHDR", &header)?;
+        let control = out.stream_position()?;
+        let mut apng = Apng {
+            out,
+            size: (width, height),
+            control,
+            sequence: 0,
+            frames: 0,
+        };
+        apng.control()?;
+        Ok(apng)
+    }
+    // acTL with the frames written so far, played endlessly
+    fn control(&mut self) -> io::Result<()> {
+        let mut data = self.frames.to_be_bytes().to_vec();
+        data.extend_from_slice(&0u32.to_be_bytes());
+        chunk(self.out, b"acTL", &data)
+    }
+    fn frame(&mut self, frame: &RgbImage, delay: u16) -> io::Result<()> {
+        let (width, height) = self.size;
+        let mut control = self.sequence.to_be_bytes().to_vec();
+        control.extend_from_slice(&width.to_be_bytes());
+        control.extend_from_slice(&height.to_be_bytes());
+        control.extend_from_slice(&[0; 8]);
+        control.extend_from_slice(&delay.to_be_bytes());
+        control.extend_from_slice(&1000u16.to_be_bytes());
+        // every frame replaces the whole image
+        control.extend_from_slice(&[0, 0]);
+        chunk(self.out, b"fcTL", &control)?;
+        self.sequence += 1;
+
+        // every row starts with filter 1, the difference with the pixel on the left
+        let row = width as usize * 3;
+        let mut filtered = Vec::with_capacity((row + 1) * height as usize);
+        for line in frame.as_raw().chunks(row) {
+            filtered.push(1);
+            for (i, byte) in line.iter().enumerate() {
+                let left = if i >= 3 { line[i - 3] } else { 0 };
+                filtered.push(byte.wrapping_sub(left));
+            }
+        }
+        let compressed = deflate::deflate_bytes_zlib(&filtered);
+        // the first frame is the default image, the others frame data
+        if self.frames == 0 {
+            chunk(self.out, b"IDAT", &compressed)?;
+        } else {
+            let mut data = self.sequence.to_be_bytes().to_vec();
+            data.extend_from_slice(&compressed);
+            chunk(self.out, b"fdAT", &data)?;
+            self.sequence += 1;
+        }
+        self.frames += 1;
+        Ok(())
+    }
+    fn finish(mut self) -> io::Result<()> {
+        chunk(self.out, b"IEND", &[])?;
+        let end = self.out.stream_position()?;
+        self.out.seek(SeekFrom::Start(self.control))?;
+        self.control()?;
+        self.out.seek(SeekFrom::Start(end))?;
+        Ok(())
+    }
+}
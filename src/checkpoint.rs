@@ -0,0 +1,59 @@
+use super::config::Config;
+use super::error::{Error, Result};
+use super::file;
+use super::mesh::Mesh;
+
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// State of an evolution between two generations, see
+/// [`Evolution::checkpoint`](crate::Evolution::checkpoint) and
+/// [`Triangulator::resume`](crate::Triangulator::resume).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Checkpoint {
+    /// Configuration of the run, used again when resuming.
+    pub config: Config,
+    /// Seed the run was started with.
+    pub seed: u32,
+    /// Number of the generation evolved next.
+    pub generation: u32,
+    /// Last accepted generation, the next one is created from its points.
+    pub mesh: Mesh,
+    // position in the random stream, so mutations continue where they left off
+    pub(crate) rng: ChaCha8Rng,
+    // hall of fame, the best mesh so far and its PSNR
+    pub(crate) best: Option<(f64, Mesh)>,
+    // best average fitness so far and generations since it was reached
+    pub(crate) best_fitness: Option<f32>,
+    pub(crate) stale: u32,
+}
+
+impl Checkpoint {
+    /// Read a checkpoint saved with [`Checkpoint::save`].
+    pub fn load(path: &Path) -> Result<Checkpoint> {
+        let contents = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let checkpoint: Checkpoint = serde_json::from_str(&contents)
+            .map_err(|e| Error::checkpoint(e.to_string()).in_file(path))?;
+        checkpoint.validate().map_err(|e| e.in_file(path))?;
+        Ok(checkpoint)
+    }
+    /// Write the checkpoint as JSON.
+    ///
+    /// The file is written next to `path` first and moved in place, so a run
+    /// killed while saving leaves the previous checkpoint intact.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string(self)
+            .map_err(|e| Error::checkpoint(e.to_string()).in_file(path))?;
+        file::replace(path, contents.as_bytes())
+    }
+    fn validate(&self) -> Result<()> {
+        self.config.validate()?;
+        // rebuilding the population validates the meshes
+        self.mesh.population(self.config.member)?;
+        if let Some((_, best)) = &self.best {
+            best.population(self.config.member)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,662 @@
+use traingle::animation;
+use traingle::codec;
+use traingle::config::{Acceptance, Config, Format};
+use traingle::export::{dxf, gltf, html, numbers, obj, pdf, plotter, ply, stl, svg};
+use traingle::image::GrayImage;
+use traingle::{Error, Result};
+
+use clap::{Args, Parser, Subcommand};
+use std::path::PathBuf;
+
+/// Evolve low-poly triangulations of images
+#[derive(Parser, Debug)]
+#[command(name = "traingle", version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Evolve a mesh for an image, writing a render of every generation
+    Evolve(EvolveArgs),
+    /// Rasterize a saved mesh
+    Render(RenderArgs),
+    /// Compare a saved mesh against an image
+    Score(ScoreArgs),
+    /// Print statistics of a saved mesh, or how a render was made
+    Inspect(InspectArgs),
+    /// Convert a saved mesh to a vector format
+    Export(ExportArgs),
+    /// Encode a saved mesh in the compact binary format
+    Encode(EncodeArgs),
+    /// Decode a mesh from the compact binary format to JSON
+    Decode(DecodeArgs),
+    /// Morph one saved mesh into another
+    Morph(MorphArgs),
+    /// Make a paint-by-numbers kit of a saved mesh
+    Numbers(NumbersArgs),
+}
+
+/// Options given on the command line override the values of --config.
+#[derive(Args, Debug)]
+pub struct EvolveArgs {
+    /// Image to triangulate
+    pub input: PathBuf,
+
+    /// Run configuration file (.toml or .json)
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+
+    /// Continue the run saved in a checkpoint, with its configuration
+    #[arg(long, conflicts_with_all = ["config", "segments", "seed"])]
+    pub resume: Option<PathBuf>,
+
+    /// Start from the points of a saved mesh instead of a grid, with the
+    /// configuration recorded in it unless --config is given
+    #[arg(long, conflicts_with = "resume")]
+    pub from: Option<PathBuf>,
+
+    /// Number of points along each side of the initial grid [default: 35]
+    #[arg(short, long)]
+    pub segments: Option<u32>,
+
+    /// Number of generations evolved after the initial one [default: 20]
+    #[arg(short, long)]
+    pub generations: Option<u32>,
+
+    /// Number of mutation rounds per generation [default: 10]
+    #[arg(short, long)]
+    pub mutations: Option<u32>,
+
+    /// Directory rendered generations are written to [default: output]
+    #[arg(short, long)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Output filename without extension, `{generation}` is replaced by the
+    /// generation number [default: output-{generation}]
+    #[arg(short, long)]
+    pub filename: Option<String>,
+
+    /// Image format of rendered generations: jpg, png, bmp or tiff [default: jpg]
+    #[arg(long)]
+    pub format: Option<Format>,
+
+    /// Seed of the mutations, a random one is picked and recorded when missing
+    #[arg(long)]
+    pub seed: Option<u32>,
+
+    /// Which generations the run continues from: always, improve (a better
+    /// PSNR than the current one) or tolerance [default: improve]
+    #[arg(long)]
+    pub acceptance: Option<Acceptance>,
+
+    /// PSNR in dB a generation may lose against the best one with
+    /// --acceptance tolerance [default: 0.5]
+    #[arg(long)]
+    pub tolerance: Option<f64>,
+
+    /// Generations between checkpoints written to the output directory, 0
+    /// disables them [default: 1]
+    #[arg(long)]
+    pub checkpoint_interval: Option<u32>,
+
+    /// Also save the mesh of every generation next to its render, as JSON
+    #[arg(long)]
+    pub save_meshes: bool,
+
+    /// Also assemble the renders into an animation in the output directory,
+    /// a .gif or an APNG (.png or .apng)
+    #[arg(long)]
+    pub animation: Option<PathBuf>,
+
+    /// Milliseconds every frame of --animation is shown [default: 200]
+    #[arg(long)]
+    pub frame_delay: Option<u16>,
+
+    /// Milliseconds the last frame of --animation is shown before it loops
+    /// [default: 2000]
+    #[arg(long)]
+    pub final_hold: Option<u16>,
+
+    /// Size of the frames of --animation relative to the image [default: 1]
+    #[arg(long)]
+    pub animation_scale: Option<f32>,
+
+    /// Frames of --animation and --y4m morphed between consecutive
+    /// generations [default: 0]
+    #[arg(long)]
+    pub tween: Option<u32>,
+
+    /// Stream the renders to stdout as YUV4MPEG2 video, progress goes to
+    /// stderr instead
+    #[arg(long)]
+    pub y4m: bool,
+
+    /// Frame rate of --y4m
+    #[arg(long, default_value_t = 10, requires = "y4m",
+          value_parser = clap::value_parser!(u32).range(1..))]
+    pub fps: u32,
+
+    /// Stop after this many seconds, the best mesh so far is still written
+    #[arg(long)]
+    pub time_limit: Option<f64>,
+
+    /// Stop after this many generations in a row without a better average fitness
+    #[arg(long)]
+    pub plateau: Option<u32>,
+
+    /// Stop once the average fitness reaches this value
+    #[arg(long)]
+    pub target_fitness: Option<f32>,
+
+    /// Stop once the render reaches this PSNR in dB against the image
+    #[arg(long)]
+    pub target_psnr: Option<f64>,
+}
+
+#[derive(Args, Debug)]
+pub struct RenderArgs {
+    /// Saved mesh
+    pub mesh: PathBuf,
+
+    /// Output image, the format is picked from the extension
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Output width, the height follows the mesh aspect ratio unless given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: Option<u32>,
+
+    /// Output height, the width follows the mesh aspect ratio unless given
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub height: Option<u32>,
+
+    /// Scale factor applied to the mesh dimensions
+    #[arg(long, conflicts_with_all = ["width", "height"])]
+    pub scale: Option<f32>,
+}
+
+#[derive(Args, Debug)]
+pub struct ScoreArgs {
+    /// Saved mesh
+    pub mesh: PathBuf,
+
+    /// Image to compare against
+    pub image: PathBuf,
+
+    /// Run configuration used to compute face fitness (.toml or .json)
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct InspectArgs {
+    /// Saved mesh, or an image rendered by evolve to print the run it comes
+    /// from
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Saved mesh, or for html every generation the viewer scrubs through
+    #[arg(required = true)]
+    pub meshes: Vec<PathBuf>,
+
+    /// Output file, the format is picked from the extension: svg, pdf, obj,
+    /// ply, glb, gltf, stl, dxf or html
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// SVG: put the shapes of each color in one group
+    #[arg(long)]
+    pub group_by_color: bool,
+
+    /// SVG, PDF: merge adjacent faces of the same color into one path
+    #[arg(long)]
+    pub merge: bool,
+
+    /// PDF: a0 to a5, letter, legal, tabloid or <width>x<height> in mm [default: a4]
+    #[arg(long)]
+    pub page: Option<pdf::PageSize>,
+
+    /// PDF: turn the page sideways
+    #[arg(long)]
+    pub landscape: bool,
+
+    /// PDF: blank space around the mesh in mm [default: 10]
+    #[arg(long)]
+    pub margin: Option<f32>,
+
+    /// PDF: extra space around the page, the mesh extends into it with --margin 0, in mm [default: 0]
+    #[arg(long)]
+    pub bleed: Option<f32>,
+
+    /// PDF: scale the mesh to cover the page and crop it instead of fitting it
+    #[arg(long)]
+    pub cover: bool,
+
+    /// OBJ: color with vertex colors instead of a material file
+    #[arg(long)]
+    pub vertex_colors: bool,
+
+    /// Height, per format. OBJ, PLY: lift points by the luminance of their
+    /// faces times this many pixels [default: flat]. STL: height of the relief
+    /// above the base in mm [default: 3]
+    #[arg(long)]
+    pub height: Option<f32>,
+
+    /// Width of the output, per format. glTF: in scene units [default: 1].
+    /// STL: in mm [default: 100]. SVG with --layers: in mm [default: 200].
+    /// PDF is sized by --page, DXF by --scale
+    #[arg(long)]
+    pub width: Option<f32>,
+
+    /// glTF: give the mesh a back and sides this thick, in scene units
+    #[arg(long)]
+    pub extrude: Option<f32>,
+
+    /// glTF: shade the faces with the scene lights instead of showing their colors as is
+    #[arg(long)]
+    pub lit: bool,
+
+    /// STL: thickness in mm under the lowest point [default: 1]
+    #[arg(long)]
+    pub base: Option<f32>,
+
+    /// STL: raise points by the brightness of this image instead of the
+    /// luminance of their faces
+    #[arg(long)]
+    pub height_map: Option<PathBuf>,
+
+    /// STL: raise dark points instead of bright ones, for a lithophane
+    #[arg(long)]
+    pub invert: bool,
+
+    /// SVG: one Inkscape layer of outlines per color, in mm, for pen
+    /// plotters and vinyl cutters
+    #[arg(long)]
+    pub layers: bool,
+
+    /// SVG with --layers, DXF: reduce the colors to a palette of this many
+    /// colors, one layer each
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub colors: Option<u16>,
+
+    /// SVG with --layers: fill regions with lines this many mm apart
+    #[arg(long)]
+    pub hatch: Option<f32>,
+
+    /// SVG with --layers: angle of the hatch lines in degrees [default: 45]
+    #[arg(long, allow_hyphen_values = true)]
+    pub hatch_angle: Option<f32>,
+
+    /// SVG with --layers: width of the pen in mm [default: 0.3]
+    #[arg(long)]
+    pub pen: Option<f32>,
+
+    /// DXF: a closed polyline per face instead of a line per edge, where
+    /// edges shared by two faces are written once
+    #[arg(long)]
+    pub faces: bool,
+
+    /// DXF: millimetres per pixel of the mesh [default: 1]. Other formats
+    /// are sized by --width or --page
+    #[arg(long)]
+    pub scale: Option<f32>,
+}
+
+#[derive(Args, Debug)]
+pub struct EncodeArgs {
+    /// Saved mesh
+    pub mesh: PathBuf,
+
+    /// Encoded output file
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Steps per pixel vertices are rounded to [default: 4]
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..))]
+    pub precision: Option<u8>,
+
+    /// Reduce the colors to a palette of this many colors
+    #[arg(long, value_parser = clap::value_parser!(u16).range(1..))]
+    pub palette: Option<u16>,
+
+    /// Original image, to compare the decoded mesh against a JPEG of the same size
+    #[arg(long)]
+    pub image: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct DecodeArgs {
+    /// Encoded mesh
+    pub input: PathBuf,
+
+    /// Output mesh (.json)
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct MorphArgs {
+    /// Saved mesh the morph starts from
+    pub from: PathBuf,
+
+    /// Saved mesh the morph ends with, frames have its dimensions
+    pub to: PathBuf,
+
+    /// An animation (.gif, or .png and .apng for APNG), or a directory the
+    /// frames are written to as PNG images
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Frames between the two meshes
+    #[arg(long, default_value_t = 24)]
+    pub frames: u32,
+
+    /// Milliseconds every frame of an animation is shown
+    #[arg(long, default_value_t = 40)]
+    pub frame_delay: u16,
+
+    /// Milliseconds the last frame of an animation is shown before it loops
+    #[arg(long, default_value_t = 2000)]
+    pub final_hold: u16,
+
+    /// Size of the frames relative to the dimensions of the last mesh
+    #[arg(long, default_value_t = 1.0)]
+    pub scale: f32,
+}
+
+#[derive(Args, Debug)]
+pub struct NumbersArgs {
+    /// Saved mesh
+    pub mesh: PathBuf,
+
+    /// Directory the sheet and legend are written to, as SVG and PNG
+    #[arg(short, long)]
+    pub output: PathBuf,
+
+    /// Number of paints the colors are reduced to
+    #[arg(long, default_value_t = 12, value_parser = clap::value_parser!(u16).range(1..))]
+    pub colors: u16,
+
+    /// Width in pixels of the PNG images
+    #[arg(long, default_value_t = 2400, value_parser = clap::value_parser!(u32).range(1..))]
+    pub width: u32,
+}
+
+impl EvolveArgs {
+    // Effective configuration of this run
+    pub fn config(&self) -> Result<Config> {
+        self.apply(load_config(&self.config)?)
+    }
+    // Override `config` with the options given on the command line
+    pub fn apply(&self, mut config: Config) -> Result<Config> {
+        if let Some(segments) = self.segments {
+            config.run.segments = segments;
+        }
+        if let Some(generations) = self.generations {
+            config.run.generations = generations;
+        }
+        if let Some(mutations) = self.mutations {
+            config.run.mutations_per_generation = mutations;
+        }
+        if let Some(dir) = &self.output_dir {
+            config.output.dir = dir.clone();
+        }
+        if let Some(filename) = &self.filename {
+            config.output.filename = filename.clone();
+        }
+        if let Some(format) = self.format {
+            config.output.format = format;
+        }
+        if let Some(acceptance) = self.acceptance {
+            config.elitism.acceptance = acceptance;
+        }
+        if let Some(tolerance) = self.tolerance {
+            config.elitism.tolerance = tolerance;
+        }
+        if let Some(interval) = self.checkpoint_interval {
+            config.output.checkpoint_interval = interval;
+        }
+        if self.save_meshes {
+            config.output.meshes = true;
+        }
+        if let Some(seed) = self.seed {
+            config.run.seed = Some(seed);
+        }
+        if let Some(file) = &self.animation {
+            config.animation.file = Some(file.clone());
+        }
+        if let Some(delay) = self.frame_delay {
+            config.animation.delay = delay;
+        }
+        if let Some(hold) = self.final_hold {
+            config.animation.hold = hold;
+        }
+        if let Some(scale) = self.animation_scale {
+            config.animation.scale = scale;
+        }
+        if let Some(tween) = self.tween {
+            config.animation.tween = tween;
+        }
+        if let Some(time_limit) = self.time_limit {
+            config.stop.time_limit = Some(time_limit);
+        }
+        if let Some(plateau) = self.plateau {
+            config.stop.plateau = Some(plateau);
+        }
+        if let Some(target) = self.target_fitness {
+            config.stop.target_fitness = Some(target);
+        }
+        if let Some(target) = self.target_psnr {
+            config.stop.target_psnr = Some(target);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl RenderArgs {
+    // Output size for a mesh of the given dimensions
+    pub fn size(&self, (width, height): (f32, f32)) -> Result<(u32, u32)> {
+        let size = match (self.width, self.height, self.scale) {
+            (Some(w), Some(h), _) => (w, h),
+            (Some(w), None, _) => (w, (w as f32 * height / width).round() as u32),
+            (None, Some(h), _) => ((h as f32 * width / height).round() as u32, h),
+            (None, None, Some(scale)) => {
+                if scale.is_nan() || scale <= 0.0 {
+                    return Err(option_error("--scale must be positive"));
+                }
+                (
+                    (width * scale).round() as u32,
+                    (height * scale).round() as u32,
+                )
+            }
+            (None, None, None) => (width as u32, height as u32),
+        };
+        if size.0 == 0 || size.1 == 0 {
+            return Err(option_error(format!(
+                "output size {}x{} is empty",
+                size.0, size.1
+            )));
+        }
+        Ok(size)
+    }
+}
+
+impl ScoreArgs {
+    pub fn config(&self) -> Result<Config> {
+        let config = load_config(&self.config)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl ExportArgs {
+    pub fn svg(&self) -> svg::Options {
+        svg::Options {
+            group_by_color: self.group_by_color,
+            merge: self.merge,
+        }
+    }
+    pub fn plotter(&self) -> plotter::Options {
+        let mut options = plotter::Options {
+            colors: self.colors.map(|k| k as usize),
+            hatch: self.hatch,
+            ..plotter::Options::default()
+        };
+        if let Some(width) = self.width {
+            options.width = width;
+        }
+        if let Some(pen) = self.pen {
+            options.pen = pen;
+        }
+        if let Some(angle) = self.hatch_angle {
+            options.hatch_angle = angle;
+        }
+        options
+    }
+    pub fn dxf(&self) -> dxf::Options {
+        let mut options = dxf::Options {
+            entities: if self.faces {
+                dxf::Entities::Polylines
+            } else {
+                dxf::Entities::Lines
+            },
+            colors: self.colors.map(|k| k as usize),
+            ..dxf::Options::default()
+        };
+        if let Some(scale) = self.scale {
+            options.scale = scale;
+        }
+        options
+    }
+    pub fn obj(&self) -> obj::Options {
+        obj::Options {
+            colors: if self.vertex_colors {
+                obj::Colors::Vertex
+            } else {
+                obj::Colors::Materials
+            },
+            height: self.height,
+            ..obj::Options::default()
+        }
+    }
+    pub fn ply(&self) -> ply::Options {
+        ply::Options {
+            height: self.height,
+        }
+    }
+    pub fn html(&self) -> html::Options {
+        let mut options = html::Options::default();
+        if let Some(name) = self.meshes[0].file_name() {
+            options.title = format!("traingle: {}", name.to_string_lossy());
+        }
+        options
+    }
+    pub fn gltf(&self) -> gltf::Options {
+        let mut options = gltf::Options {
+            extrude: self.extrude,
+            unlit: !self.lit,
+            ..gltf::Options::default()
+        };
+        if let Some(width) = self.width {
+            options.width = width;
+        }
+        options
+    }
+    // `map` is the image of --height-map
+    pub fn stl(&self, map: Option<GrayImage>) -> stl::Options {
+        let mut options = stl::Options {
+            invert: self.invert,
+            ..stl::Options::default()
+        };
+        if let Some(map) = map {
+            options.heights = stl::Heights::Map(map);
+        }
+        if let Some(width) = self.width {
+            options.width = width;
+        }
+        if let Some(base) = self.base {
+            options.base = base;
+        }
+        if let Some(height) = self.height {
+            options.height = height;
+        }
+        options
+    }
+    pub fn pdf(&self) -> pdf::Options {
+        let mut options = pdf::Options {
+            cover: self.cover,
+            merge: self.merge,
+            ..pdf::Options::default()
+        };
+        if let Some(page) = self.page {
+            options.page = page;
+        }
+        if self.landscape {
+            options.page = options.page.landscape();
+        }
+        if let Some(margin) = self.margin {
+            options.margin = margin;
+        }
+        if let Some(bleed) = self.bleed {
+            options.bleed = bleed;
+        }
+        options
+    }
+}
+
+impl EncodeArgs {
+    pub fn codec(&self) -> codec::Options {
+        let mut options = codec::Options {
+            palette: self.palette.map(|k| k as usize),
+            ..codec::Options::default()
+        };
+        if let Some(precision) = self.precision {
+            options.precision = precision;
+        }
+        options
+    }
+}
+
+impl NumbersArgs {
+    pub fn numbers(&self) -> numbers::Options {
+        numbers::Options {
+            colors: self.colors as usize,
+        }
+    }
+}
+
+impl MorphArgs {
+    pub fn timing(&self) -> animation::Options {
+        animation::Options {
+            delay: self.frame_delay,
+            hold: self.final_hold,
+        }
+    }
+    // Frame size for a last mesh of the given dimensions
+    pub fn size(&self, (width, height): (u32, u32)) -> Result<(u32, u32)> {
+        if self.scale.is_nan() || self.scale <= 0.0 {
+            return Err(option_error("--scale must be positive"));
+        }
+        let scale = |n: u32| ((n as f32 * self.scale).round() as u32).max(1);
+        Ok((scale(width), scale(height)))
+    }
+}
+
+fn load_config(path: &Option<PathBuf>) -> Result<Config> {
+    match path {
+        Some(path) => Config::load(path),
+        None => Ok(Config::default()),
+    }
+}
+
+fn option_error<S: Into<String>>(message: S) -> Error {
+    Error::Config {
+        path: None,
+        message: message.into(),
+    }
+}
@@ -0,0 +1,301 @@
+//! Compact binary encoding of meshes.
+//!
+//! Only the vertices and the face colors are stored: vertices are quantized,
+//! sorted and delta coded as varints, and the triangles are recovered with a
+//! Delaunay triangulation when decoding. Colors go through a color table,
+//! optionally reduced to a smaller palette.
+//!
+//! ```text
+//! magic       "TRGL"
+//! version     u8, 1
+//! width       varint
+//! height      varint
+//! precision   u8, steps per pixel the vertices are quantized to
+//! points      varint count, then per point zigzag varint dx and dy in steps
+//!             from the previous point, the first one from (0, 0)
+//! colors      varint count, then r, g, b bytes per color
+//! faces       varint count, then a color index per face in triangulation
+//!             order, a byte when there are at most 256 colors, else a varint
+//! ```
+
+use crate::error::{Error, Result};
+use crate::geom::{Point, Triangle};
+use crate::mesh::{self, FaceGrid, Mesh};
+use crate::palette;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"TRGL";
+const VERSION: u8 = 1;
+
+/// Options of [`encode`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Steps per pixel vertices are rounded to, 1 keeps whole pixels.
+    pub precision: u8,
+    /// Reduce the colors to a palette of this many colors.
+    pub palette: Option<usize>,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            precision: 4,
+            palette: None,
+        }
+    }
+}
+
+/// Encode `mesh`, see the [module docs](self) for the layout.
+///
+/// Colors are picked again for the triangulation of the quantized vertices,
+/// averaging the faces of `mesh` each new face covers, so
+/// [`decode`] gives back the mesh as it renders rather than its exact
+/// triangles.
+pub fn encode(mesh: &Mesh, options: &Options) -> Vec<u8> {
+    let precision = options.precision.max(1);
+    let step = precision as f32;
+    let mut quantized: Vec<(i64, i64)> = mesh
+        .points
+        .iter()
+        .map(|(x, y)| ((x * step).round() as i64, (y * step).round() as i64))
+        .collect();
+    quantized.sort_unstable();
+    quantized.dedup();
+    let points: Vec<(f32, f32)> = quantized
+        .iter()
+        .map(|(x, y)| (*x as f32 / step, *y as f32 / step))
+        .collect();
+    let triangles = mesh::triangulate(&points);
+    let grid = FaceGrid::new(mesh);
+    let colors: Vec<[u8; 3]> = triangles
+        .iter()
+        .map(|t| average_color(&grid, &points, t))
+        .collect();
+
+    let (table, indices) = match options.palette {
+        Some(k) => {
+            let areas: Vec<f32> = triangles
+                .iter()
+                .map(|t| triangle(&points, t).area())
+                .collect();
+            palette::reduce(&colors, &areas, k)
+        }
+        None => {
+            let mut table = vec![];
+            let mut seen: HashMap<[u8; 3], usize> = HashMap::new();
+            let indices = colors
+                .iter()
+                .map(|color| {
+                    *seen.entry(*color).or_insert_with(|| {
+                        table.push(*color);
+                        table.len() - 1
+                    })
+                })
+                .collect();
+            (table, indices)
+        }
+    };
+
+    let mut out = MAGIC.to_vec();
+    out.push(VERSION);
+    write_varint(&mut out, mesh.width as u64);
+    write_varint(&mut out, mesh.height as u64);
+    out.push(precision);
+    write_varint(&mut out, quantized.len() as u64);
+    let mut previous = (0, 0);
+    for point in &quantized {
+        write_varint(&mut out, zigzag(point.0 - previous.0));
+        write_varint(&mut out, zigzag(point.1 - previous.1));
+        previous = *point;
+    }
+    write_varint(&mut out, table.len() as u64);
+    for color in &table {
+        out.extend_from_slice(color);
+    }
+    write_varint(&mut out, indices.len() as u64);
+    for index in indices {
+        if table.len() <= 256 {
+            out.push(index as u8);
+        } else {
+            write_varint(&mut out, index as u64);
+        }
+    }
+    out
+}
+
+/// Decode a mesh encoded with [`encode`].
+pub fn decode(data: &[u8]) -> Result<Mesh> {
+    let mut reader = Reader { data, position: 0 };
+    if reader.bytes(4)? != MAGIC {
+        return Err(Error::mesh("not an encoded mesh"));
+    }
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(Error::mesh(format!(
+            "encoding version {} is not supported",
+            version
+        )));
+    }
+    let width = reader.dimension()?;
+    let height = reader.dimension()?;
+    let precision = reader.byte()?.max(1) as f32;
+
+    let n_points = reader.count()?;
+    let mut points = Vec::with_capacity(n_points);
+    let mut previous = (0i64, 0i64);
+    for _ in 0..n_points {
+        let dx = unzigzag(reader.varint()?);
+        let dy = unzigzag(reader.varint()?);
+        previous = (previous.0 + dx, previous.1 + dy);
+        points.push((previous.0 as f32 / precision, previous.1 as f32 / precision));
+    }
+    let n_colors = reader.count()?;
+    let mut table = Vec::with_capacity(n_colors);
+    for _ in 0..n_colors {
+        let rgb = reader.bytes(3)?;
+        table.push([rgb[0], rgb[1], rgb[2]]);
+    }
+
+    let triangles = mesh::triangulate(&points);
+    let n_faces = reader.count()?;
+    if n_faces != triangles.len() {
+        return Err(Error::mesh(format!(
+            "encoded mesh has {} faces but its points triangulate into {}",
+            n_faces,
+            triangles.len()
+        )));
+    }
+    let mut colors = Vec::with_capacity(n_faces);
+    for _ in 0..n_faces {
+        let index = if table.len() <= 256 {
+            reader.byte()? as usize
+        } else {
+            reader.varint()? as usize
+        };
+        let color = table
+            .get(index)
+            .ok_or_else(|| Error::mesh(format!("color {} is not in the color table", index)))?;
+        colors.push(*color);
+    }
+
+    Ok(Mesh {
+        version: mesh::FORMAT_VERSION,
+        width,
+        height,
+        points,
+        triangles,
+        colors,
+        fitness: vec![],
+        run: None,
+        origins: vec![],
+    })
+}
+
+/// Encode `mesh` to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    std::fs::write(path, encode(mesh, options)).map_err(|e| Error::io(path, e))
+}
+
+/// Decode the mesh encoded in `path`.
+pub fn load(path: &Path) -> Result<Mesh> {
+    let data = std::fs::read(path).map_err(|e| Error::io(path, e))?;
+    decode(&data).map_err(|e| e.in_file(path))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + n)
+            .ok_or_else(|| Error::mesh("encoded mesh is truncated"))?;
+        self.position += n;
+        Ok(bytes)
+    }
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::mesh("encoded mesh has a varint over 64 bits"))
+    }
+    // Number of items that follow, each takes at least a byte
+    fn count(&mut self) -> Result<usize> {
+        let value = self.varint()?;
+        if value > (self.data.len() - self.position) as u64 {
+            return Err(Error::mesh("encoded mesh is truncated"));
+        }
+        Ok(value as usize)
+    }
+    fn dimension(&mut self) -> Result<u32> {
+        let value = self.varint()?;
+        u32::try_from(value)
+            .ok()
+            .filter(|v| *v > 0)
+            .ok_or_else(|| Error::mesh(format!("encoded dimension {} is invalid", value)))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn triangle(points: &[(f32, f32)], [a, b, c]: &[usize; 3]) -> Triangle {
+    Triangle::from_points(
+        Point::from(points[*a]),
+        Point::from(points[*b]),
+        Point::from(points[*c]),
+    )
+}
+
+// Average color of `mesh` over a grid of points inside the triangle, the
+// quantized points may triangulate differently where points are co-circular
+fn average_color(grid: &FaceGrid, points: &[(f32, f32)], [a, b, c]: &[usize; 3]) -> [u8; 3] {
+    const STEPS: usize = 4;
+    let (pa, pb, pc) = (points[*a], points[*b], points[*c]);
+    let mut sum = [0u32; 3];
+    let mut n = 0;
+    for i in 0..STEPS {
+        for j in 0..STEPS - i {
+            // barycentric coordinates of the centers of the sub-triangles
+            let u = (i as f32 + 1.0 / 3.0) / STEPS as f32;
+            let v = (j as f32 + 1.0 / 3.0) / STEPS as f32;
+            let w = 1.0 - u - v;
+            let point = (
+                u * pa.0 + v * pb.0 + w * pc.0,
+                u * pa.1 + v * pb.1 + w * pc.1,
+            );
+            for (total, channel) in sum.iter_mut().zip(grid.color_at(point)) {
+                *total += channel as u32;
+            }
+            n += 1;
+        }
+    }
+    sum.map(|total| ((total + n / 2) / n) as u8)
+}
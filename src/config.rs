@@ -0,0 +1,400 @@
+use super::animation;
+use super::error::{Error, Result};
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const GENERATION_PLACEHOLDER: &str = "{generation}";
+
+// Every tuning value of a run. Loaded from TOML or JSON, missing values
+//  fall back to the defaults below.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub run: RunConfig,
+    pub output: OutputConfig,
+    pub generation: GenerationConfig,
+    pub member: MemberConfig,
+    pub group: GroupConfig,
+    pub stop: StopConfig,
+    pub elitism: ElitismConfig,
+    pub animation: AnimationConfig,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunConfig {
+    // number of points along each side of the initial grid
+    pub segments: u32,
+    // generations evolved after the initial one
+    pub generations: u32,
+    pub mutations_per_generation: u32,
+    // seed of the mutation RNG, a random one is picked when missing
+    pub seed: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct OutputConfig {
+    pub dir: PathBuf,
+    // filename without extension, {generation} is replaced by the generation number
+    pub filename: String,
+    pub format: Format,
+    // generations between checkpoints written to the output dir, 0 disables them
+    pub checkpoint_interval: u32,
+    // also save the mesh of every generation next to its render, as {filename}.json
+    pub meshes: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct GenerationConfig {
+    // faces with a smaller area (in pixels) are not used to pick the best points,
+    // 0 uses every face
+    pub face_size_threshold: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemberConfig {
+    // scale of the normally distributed mutation delta
+    pub max_mutation_value: f32,
+    // probability that a member is mutated in a mutation round
+    pub mutation_frequency: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct GroupConfig {
+    // max distance used to group pixels into bins
+    pub beneficial_distance: f32,
+    // if main bin contains less than this percent of total pixels,
+    //  pixels are used from multiple bins to calculate color/fitness
+    pub min_pixel_percent: f32,
+    // bin must contain this percent of total pixels to be considered substantial
+    pub substantial_bin_area_percent_threshold: f32,
+    // entire group must contain this many pixels to have fitness
+    pub total_group_size_threshold: i32,
+}
+
+// Criteria that end a run before run.generations, every one is optional
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StopConfig {
+    // wall-clock budget in seconds, checked before each generation
+    pub time_limit: Option<f64>,
+    // generations in a row without improving the average fitness
+    pub plateau: Option<u32>,
+    // average fitness of the best members to reach
+    pub target_fitness: Option<f32>,
+    // PSNR in dB of the rendered mesh against the image to reach
+    pub target_psnr: Option<f64>,
+}
+
+// Which evolved generations the run continues from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct ElitismConfig {
+    pub acceptance: Acceptance,
+    // PSNR in dB a generation may lose against the best one with Acceptance::Tolerance
+    pub tolerance: f64,
+}
+
+// Rule deciding if a generation replaces the current one, judged by the PSNR
+// of its render against the image
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Acceptance {
+    // every generation is accepted, even when it is worse
+    Always,
+    // only generations better than the current one are accepted
+    Improve,
+    // generations within elitism.tolerance of the best one are accepted
+    Tolerance,
+}
+
+// Every generation's render assembled into one animation as the run goes
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationConfig {
+    // .gif, or .png/.apng for APNG, in output.dir; no animation when missing
+    pub file: Option<PathBuf>,
+    // milliseconds every frame is shown
+    pub delay: u16,
+    // milliseconds the last frame is shown before looping
+    pub hold: u16,
+    // size of the frames relative to the image
+    pub scale: f32,
+    // frames morphed between consecutive generations, also in evolve --y4m
+    pub tween: u32,
+}
+
+impl Default for RunConfig {
+    fn default() -> RunConfig {
+        RunConfig {
+            segments: 35,
+            generations: 20,
+            mutations_per_generation: 10,
+            seed: None,
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> OutputConfig {
+        OutputConfig {
+            dir: PathBuf::from("output"),
+            filename: format!("output-{}", GENERATION_PLACEHOLDER),
+            format: Format::Jpg,
+            checkpoint_interval: 1,
+            meshes: false,
+        }
+    }
+}
+
+impl Default for ElitismConfig {
+    fn default() -> ElitismConfig {
+        ElitismConfig {
+            acceptance: Acceptance::Improve,
+            tolerance: 0.5,
+        }
+    }
+}
+
+impl Default for AnimationConfig {
+    fn default() -> AnimationConfig {
+        let timing = animation::Options::default();
+        AnimationConfig {
+            file: None,
+            delay: timing.delay,
+            hold: timing.hold,
+            scale: 1.0,
+            tween: 0,
+        }
+    }
+}
+
+impl Default for GenerationConfig {
+    fn default() -> GenerationConfig {
+        GenerationConfig {
+            face_size_threshold: 0.0,
+        }
+    }
+}
+
+impl Default for MemberConfig {
+    fn default() -> MemberConfig {
+        MemberConfig {
+            max_mutation_value: 5.0,
+            mutation_frequency: 3.0 / 5.0,
+        }
+    }
+}
+
+impl Default for GroupConfig {
+    fn default() -> GroupConfig {
+        GroupConfig {
+            beneficial_distance: 150.0,
+            min_pixel_percent: 0.95,
+            substantial_bin_area_percent_threshold: 0.01,
+            total_group_size_threshold: 10,
+        }
+    }
+}
+
+impl Config {
+    // Format is picked from the extension, anything but .json is read as TOML
+    pub fn load(path: &Path) -> Result<Config> {
+        let contents = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let config: Config = if is_json(path) {
+            serde_json::from_str(&contents).map_err(|e| Error::config(e.to_string()))
+        } else {
+            toml::from_str(&contents).map_err(|e| Error::config(e.to_string()))
+        }
+        .map_err(|e| e.in_file(path))?;
+        Ok(config)
+    }
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = if is_json(path) {
+            serde_json::to_string_pretty(self).map_err(|e| Error::config(e.to_string()))
+        } else {
+            toml::to_string_pretty(self).map_err(|e| Error::config(e.to_string()))
+        }
+        .map_err(|e| e.in_file(path))?;
+        std::fs::write(path, contents).map_err(|e| Error::io(path, e))
+    }
+    pub fn validate(&self) -> Result<()> {
+        if self.run.segments < 2 {
+            return Err(Error::config("run.segments must be at least 2"));
+        }
+        if self.run.mutations_per_generation < 1 {
+            return Err(Error::config(
+                "run.mutations_per_generation must be at least 1",
+            ));
+        }
+        self.output.validate()?;
+        if !(0.0..).contains(&self.generation.face_size_threshold) {
+            return Err(Error::config(
+                "generation.face_size_threshold must not be negative",
+            ));
+        }
+        if !(0.0..).contains(&self.member.max_mutation_value) {
+            return Err(Error::config(
+                "member.max_mutation_value must not be negative",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.member.mutation_frequency) {
+            return Err(Error::config(
+                "member.mutation_frequency must be between 0 and 1",
+            ));
+        }
+        if self.group.beneficial_distance.is_nan() || self.group.beneficial_distance <= 0.0 {
+            return Err(Error::config("group.beneficial_distance must be positive"));
+        }
+        if !(self.group.min_pixel_percent > 0.0 && self.group.min_pixel_percent <= 1.0) {
+            return Err(Error::config(
+                "group.min_pixel_percent must be greater than 0 and at most 1",
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.group.substantial_bin_area_percent_threshold) {
+            return Err(Error::config(
+                "group.substantial_bin_area_percent_threshold must be between 0 and 1",
+            ));
+        }
+        if let Some(time_limit) = self.stop.time_limit {
+            if time_limit.is_nan() || time_limit <= 0.0 {
+                return Err(Error::config("stop.time_limit must be positive"));
+            }
+        }
+        if self.stop.plateau == Some(0) {
+            return Err(Error::config("stop.plateau must be at least 1"));
+        }
+        if !(0.0..).contains(&self.elitism.tolerance) {
+            return Err(Error::config("elitism.tolerance must not be negative"));
+        }
+        self.animation.validate()?;
+        Ok(())
+    }
+}
+
+impl AnimationConfig {
+    pub fn timing(&self) -> animation::Options {
+        animation::Options {
+            delay: self.delay,
+            hold: self.hold,
+        }
+    }
+    // Size of the frames of an image of `(width, height)`
+    pub fn size(&self, (width, height): (u32, u32)) -> (u32, u32) {
+        let scale = |n: u32| ((n as f32 * self.scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+    fn validate(&self) -> Result<()> {
+        if let Some(file) = &self.file {
+            if animation::Format::from_path(file).is_none() {
+                return Err(Error::config(
+                    "animation.file must be a .gif, .png or .apng file",
+                ));
+            }
+        }
+        if self.scale.is_nan() || self.scale <= 0.0 {
+            return Err(Error::config("animation.scale must be positive"));
+        }
+        Ok(())
+    }
+}
+
+impl OutputConfig {
+    pub fn path(&self, generation: u32) -> PathBuf {
+        self.path_with_extension(generation, self.format.extension())
+    }
+    // Where output.meshes saves the mesh of `generation`
+    pub fn mesh_path(&self, generation: u32) -> PathBuf {
+        self.path_with_extension(generation, "json")
+    }
+    fn path_with_extension(&self, generation: u32, extension: &str) -> PathBuf {
+        let name = self
+            .filename
+            .replace(GENERATION_PLACEHOLDER, &generation.to_string());
+        self.dir.join(format!("{}.{}", name, extension))
+    }
+    // every generation is written, so the template must tell them apart
+    fn validate(&self) -> Result<()> {
+        if self.filename.contains('/') || self.filename.contains('\\') {
+            return Err(Error::config(
+                "output.filename must not contain a path separator, use output.dir",
+            ));
+        }
+        if !self.filename.contains(GENERATION_PLACEHOLDER) {
+            return Err(Error::config(format!(
+                "output.filename must contain {}",
+                GENERATION_PLACEHOLDER
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Jpg,
+    Png,
+    Bmp,
+    Tiff,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Jpg => "jpg",
+            Format::Png => "png",
+            Format::Bmp => "bmp",
+            Format::Tiff => "tiff",
+        }
+    }
+    pub fn image_format(&self) -> image::ImageFormat {
+        match self {
+            Format::Jpg => image::ImageFormat::Jpeg,
+            Format::Png => image::ImageFormat::Png,
+            Format::Bmp => image::ImageFormat::Bmp,
+            Format::Tiff => image::ImageFormat::Tiff,
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Format, String> {
+        match s.to_lowercase().as_str() {
+            "jpg" | "jpeg" => Ok(Format::Jpg),
+            "png" => Ok(Format::Png),
+            "bmp" => Ok(Format::Bmp),
+            "tif" | "tiff" => Ok(Format::Tiff),
+            _ => Err(format!(
+                "unknown format {}, expected jpg, png, bmp or tiff",
+                s
+            )),
+        }
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+impl FromStr for Acceptance {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<Acceptance, String> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(Acceptance::Always),
+            "improve" => Ok(Acceptance::Improve),
+            "tolerance" => Ok(Acceptance::Tolerance),
+            _ => Err(format!(
+                "unknown acceptance rule {}, expected always, improve or tolerance",
+                s
+            )),
+        }
+    }
+}
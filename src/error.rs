@@ -0,0 +1,189 @@
+use crate::triangulator::Stop;
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while evolving, reading or writing a mesh.
+#[derive(Debug)]
+pub enum Error {
+    /// An input image could not be decoded.
+    Decode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// An output image could not be encoded.
+    Encode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// A file could not be read or written.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A run configuration is malformed or out of range.
+    Config {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// A saved mesh is malformed.
+    Mesh {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// A saved checkpoint is malformed or does not fit the run.
+    Checkpoint {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// The run metadata of a render is malformed.
+    Metadata {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// The triangulation produced a face the engine can not work with.
+    Geometry(String),
+    /// The run stopped before the initial generation was evolved.
+    Stopped(Stop),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// An I/O error about the file at `path`.
+    pub fn io(path: &Path, source: std::io::Error) -> Error {
+        Error::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+    pub(crate) fn config<S: Into<String>>(message: S) -> Error {
+        Error::Config {
+            path: None,
+            message: message.into(),
+        }
+    }
+    pub(crate) fn mesh<S: Into<String>>(message: S) -> Error {
+        Error::Mesh {
+            path: None,
+            message: message.into(),
+        }
+    }
+    pub(crate) fn checkpoint<S: Into<String>>(message: S) -> Error {
+        Error::Checkpoint {
+            path: None,
+            message: message.into(),
+        }
+    }
+    pub(crate) fn metadata<S: Into<String>>(message: S) -> Error {
+        Error::Metadata {
+            path: None,
+            message: message.into(),
+        }
+    }
+    /// File the error is about, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Decode { path, .. } | Error::Encode { path, .. } | Error::Io { path, .. } => {
+                Some(path)
+            }
+            Error::Config { path, .. }
+            | Error::Mesh { path, .. }
+            | Error::Checkpoint { path, .. }
+            | Error::Metadata { path, .. } => path.as_deref(),
+            Error::Geometry(_) | Error::Stopped(_) => None,
+        }
+    }
+    // Attach the file a config, mesh, checkpoint or metadata error was found in
+    pub(crate) fn in_file(self, file: &Path) -> Error {
+        match self {
+            Error::Config {
+                path: None,
+                message,
+            } => Error::Config {
+                path: Some(file.to_path_buf()),
+                message,
+            },
+            Error::Mesh {
+                path: None,
+                message,
+            } => Error::Mesh {
+                path: Some(file.to_path_buf()),
+                message,
+            },
+            Error::Checkpoint {
+                path: None,
+                message,
+            } => Error::Checkpoint {
+                path: Some(file.to_path_buf()),
+                message,
+            },
+            Error::Metadata {
+                path: None,
+                message,
+            } => Error::Metadata {
+                path: Some(file.to_path_buf()),
+                message,
+            },
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Decode { path, source } => {
+                write!(f, "could not decode {}: {}", path.display(), source)
+            }
+            Error::Encode { path, source } => {
+                write!(f, "could not write {}: {}", path.display(), source)
+            }
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::Config {
+                path: Some(path),
+                message,
+            } => write!(f, "invalid config {}: {}", path.display(), message),
+            Error::Config {
+                path: None,
+                message,
+            } => write!(f, "invalid config: {}", message),
+            Error::Mesh {
+                path: Some(path),
+                message,
+            } => write!(f, "invalid mesh {}: {}", path.display(), message),
+            Error::Mesh {
+                path: None,
+                message,
+            } => write!(f, "invalid mesh: {}", message),
+            Error::Checkpoint {
+                path: Some(path),
+                message,
+            } => write!(f, "invalid checkpoint {}: {}", path.display(), message),
+            Error::Checkpoint {
+                path: None,
+                message,
+            } => write!(f, "invalid checkpoint: {}", message),
+            Error::Metadata {
+                path: Some(path),
+                message,
+            } => write!(f, "invalid metadata in {}: {}", path.display(), message),
+            Error::Metadata {
+                path: None,
+                message,
+            } => write!(f, "invalid metadata: {}", message),
+            Error::Geometry(message) => write!(f, "degenerate geometry: {}", message),
+            Error::Stopped(stop) => write!(f, "no generation evolved: {}", stop),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Decode { source, .. } | Error::Encode { source, .. } => Some(source),
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,501 @@
+//! DXF drawings for laser cutters and CAD.
+//!
+//! Coordinates are in millimetres with y pointing up, and every face color
+//! has a layer, so a cutter can set the power or order of each color.
+
+use super::{number, save_with};
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+use crate::palette;
+
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Entities the mesh is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entities {
+    /// A `LINE` per edge, edges shared by two faces are drawn once, on the
+    /// layer of the first face.
+    Lines,
+    /// A closed `LWPOLYLINE` per face.
+    Polylines,
+}
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub entities: Entities,
+    /// Reduce the colors to a palette of at most this many colors first,
+    /// one layer each.
+    pub colors: Option<usize>,
+    /// Millimetres per pixel of the image the mesh was evolved on.
+    pub scale: f32,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            entities: Entities::Lines,
+            colors: None,
+            scale: 1.0,
+        }
+    }
+}
+
+const CONTINUOUS: &str = "Continuous";
+const MODEL_SPACE: &str = "*Model_Space";
+const PAPER_SPACE: &str = "*Paper_Space";
+
+/// Write `mesh` as a DXF drawing, AutoCAD 2000 (AC1015).
+///
+/// Besides the layers and entities the drawing has the tables, blocks and
+/// objects AutoCAD expects of every file of that version.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let quantized;
+    let mesh = match options.colors {
+        Some(k) => {
+            quantized = palette::quantize(mesh, k).0;
+            &quantized
+        }
+        None => mesh,
+    };
+    let point = |i: usize| {
+        let (x, y) = mesh.points[i];
+        (
+            number(x * options.scale),
+            number((mesh.height as f32 - y) * options.scale),
+        )
+    };
+
+    // layers in the order of their first face
+    let mut layers: Vec<[u8; 3]> = vec![];
+    for color in &mesh.colors {
+        if !layers.contains(color) {
+            layers.push(*color);
+        }
+    }
+
+    // everything after the header, which ends with the next free handle
+    let mut handles = Handles(0);
+    let mut dxf = String::new();
+    section(&mut dxf, "CLASSES");
+    pairs(&mut dxf, &[(0, "ENDSEC")]);
+
+    let spaces = tables(&mut dxf, &mut handles, &layers);
+    blocks(&mut dxf, &mut handles, &spaces);
+
+    section(&mut dxf, "ENTITIES");
+    let model_space = &spaces[0].1;
+    match options.entities {
+        Entities::Lines => {
+            let mut drawn = HashSet::new();
+            for (triangle, color) in mesh.triangles.iter().zip(&mesh.colors) {
+                for k in 0..3 {
+                    let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                    if !drawn.insert((a.min(b), a.max(b))) {
+                        continue;
+                    }
+                    let ((x0, y0), (x1, y1)) = (point(a), point(b));
+                    entity(
+                        &mut dxf,
+                        "LINE",
+                        &mut handles,
+                        model_space,
+                        &layer(*color),
+                        "AcDbLine",
+                    );
+                    pairs(
+                        &mut dxf,
+                        &[
+                            (10, &x0),
+                            (20, &y0),
+                            (30, "0"),
+                            (11, &x1),
+                            (21, &y1),
+                            (31, "0"),
+                        ],
+                    );
+                }
+            }
+        }
+        Entities::Polylines => {
+            for (triangle, color) in mesh.triangles.iter().zip(&mesh.colors) {
+                entity(
+                    &mut dxf,
+                    "LWPOLYLINE",
+                    &mut handles,
+                    model_space,
+                    &layer(*color),
+                    "AcDbPolyline",
+                );
+                // three vertices, closed
+                pairs(&mut dxf, &[(90, "3"), (70, "1")]);
+                for i in triangle {
+                    let (x, y) = point(*i);
+                    pairs(&mut dxf, &[(10, &x), (20, &y)]);
+                }
+            }
+        }
+    }
+    pairs(&mut dxf, &[(0, "ENDSEC")]);
+
+    objects(&mut dxf, &mut handles);
+    pairs(&mut dxf, &[(0, "EOF")]);
+
+    let mut header = String::new();
+    section(&mut header, "HEADER");
+    let (width, height) = (
+        number(mesh.width as f32 * options.scale),
+        number(mesh.height as f32 * options.scale),
+    );
+    pairs(
+        &mut header,
+        &[
+            (9, "$ACADVER"),
+            (1, "AC1015"),
+            // millimetres, metric
+            (9, "$INSUNITS"),
+            (70, "4"),
+            (9, "$MEASUREMENT"),
+            (70, "1"),
+            (9, "$EXTMIN"),
+            (10, "0"),
+            (20, "0"),
+            (30, "0"),
+            (9, "$EXTMAX"),
+            (10, &width),
+            (20, &height),
+            (30, "0"),
+            (9, "$HANDSEED"),
+            (5, &handles.peek()),
+        ],
+    );
+    pairs(&mut header, &[(0, "ENDSEC")]);
+    out.write_all(header.as_bytes())?;
+    out.write_all(dxf.as_bytes())
+}
+
+/// Write `mesh` as a DXF drawing to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    validate(options).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| write(mesh, options, out))
+}
+
+fn validate(options: &Options) -> io::Result<()> {
+    if options.scale.is_nan() || options.scale <= 0.0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the scale must be positive",
+        ));
+    }
+    Ok(())
+}
+
+// Group codes and their values, a line each
+fn pairs(dxf: &mut String, pairs: &[(u16, &str)]) {
+    for (code, value) in pairs {
+        let _ = writeln!(dxf, "{:>3}\n{}", code, value);
+    }
+}
+
+fn section(dxf: &mut String, name: &str) {
+    pairs(dxf, &[(0, "SECTION"), (2, name)]);
+}
+
+// Tables of line types, layers and the other symbols, returns the names of
+// the blocks of model and paper space and the handles of their records
+fn tables(
+    dxf: &mut String,
+    handles: &mut Handles,
+    layers: &[[u8; 3]],
+) -> Vec<(&'static str, String)> {
+    section(dxf, "TABLES");
+    table(dxf, "VPORT", handles, 0);
+    end_table(dxf);
+
+    let ltype = table(dxf, "LTYPE", handles, 3);
+    for (name, description) in &[("ByBlock", ""), ("ByLayer", ""), (CONTINUOUS, "Solid line")] {
+        record(
+            dxf,
+            "LTYPE",
+            handles,
+            &ltype,
+            "AcDbLinetypeTableRecord",
+            name,
+        );
+        pairs(dxf, &[(3, description), (72, "65"), (73, "0"), (40, "0.0")]);
+    }
+    end_table(dxf);
+
+    let layer_table = table(dxf, "LAYER", handles, 1 + layers.len());
+    // layer 0 always exists
+    record(
+        dxf,
+        "LAYER",
+        handles,
+        &layer_table,
+        "AcDbLayerTableRecord",
+        "0",
+    );
+    pairs(dxf, &[(62, "7"), (6, CONTINUOUS)]);
+    for color in layers {
+        let [r, g, b] = *color;
+        record(
+            dxf,
+            "LAYER",
+            handles,
+            &layer_table,
+            "AcDbLayerTableRecord",
+            &layer(*color),
+        );
+        pairs(
+            dxf,
+            &[
+                // white, or black on a light background, for programs
+                // without true colors
+                (62, "7"),
+                (
+                    420,
+                    &((r as u32) << 16 | (g as u32) << 8 | b as u32).to_string(),
+                ),
+                (6, CONTINUOUS),
+            ],
+        );
+    }
+    end_table(dxf);
+
+    let style = table(dxf, "STYLE", handles, 1);
+    record(
+        dxf,
+        "STYLE",
+        handles,
+        &style,
+        "AcDbTextStyleTableRecord",
+        "Standard",
+    );
+    pairs(
+        dxf,
+        &[
+            (40, "0.0"),
+            (41, "1.0"),
+            (50, "0.0"),
+            (71, "0"),
+            (42, "2.5"),
+            (3, "txt"),
+            (4, ""),
+        ],
+    );
+    end_table(dxf);
+
+    table(dxf, "VIEW", handles, 0);
+    end_table(dxf);
+    table(dxf, "UCS", handles, 0);
+    end_table(dxf);
+
+    let appid = table(dxf, "APPID", handles, 1);
+    record(
+        dxf,
+        "APPID",
+        handles,
+        &appid,
+        "AcDbRegAppTableRecord",
+        "ACAD",
+    );
+    end_table(dxf);
+
+    let dimstyle = table(dxf, "DIMSTYLE", handles, 1);
+    pairs(dxf, &[(100, "AcDbDimStyleTable")]);
+    // dimension styles have their handle in 105 instead of 5
+    pairs(
+        dxf,
+        &[
+            (0, "DIMSTYLE"),
+            (105, &handles.next()),
+            (330, &dimstyle),
+            (100, "AcDbSymbolTableRecord"),
+            (100, "AcDbDimStyleTableRecord"),
+            (2, "Standard"),
+            (70, "0"),
+        ],
+    );
+    end_table(dxf);
+
+    let block_records = table(dxf, "BLOCK_RECORD", handles, 2);
+    let mut spaces = vec![];
+    for name in &[MODEL_SPACE, PAPER_SPACE] {
+        spaces.push((*name, handles.peek()));
+        record(
+            dxf,
+            "BLOCK_RECORD",
+            handles,
+            &block_records,
+            "AcDbBlockTableRecord",
+            name,
+        );
+    }
+    end_table(dxf);
+    pairs(dxf, &[(0, "ENDSEC")]);
+    spaces
+}
+
+// Blocks of model and paper space, which every drawing has
+fn blocks(dxf: &mut String, handles: &mut Handles, spaces: &[(&str, String)]) {
+    section(dxf, "BLOCKS");
+    for (name, owner) in spaces {
+        // paper space entities are marked as such
+        let paper: &[(u16, &str)] = if *name == PAPER_SPACE {
+            &[(67, "1")]
+        } else {
+            &[]
+        };
+        pairs(
+            dxf,
+            &[
+                (0, "BLOCK"),
+                (5, &handles.next()),
+                (330, owner),
+                (100, "AcDbEntity"),
+            ],
+        );
+        pairs(dxf, paper);
+        pairs(
+            dxf,
+            &[
+                (8, "0"),
+                (100, "AcDbBlockBegin"),
+                (2, name),
+                (70, "0"),
+                (10, "0"),
+                (20, "0"),
+                (30, "0"),
+                (3, name),
+                (1, ""),
+            ],
+        );
+        pairs(
+            dxf,
+            &[
+                (0, "ENDBLK"),
+                (5, &handles.next()),
+                (330, owner),
+                (100, "AcDbEntity"),
+            ],
+        );
+        pairs(dxf, paper);
+        pairs(dxf, &[(8, "0"), (100, "AcDbBlockEnd")]);
+    }
+    pairs(dxf, &[(0, "ENDSEC")]);
+}
+
+// Root dictionary, with the dictionary of groups AutoCAD expects
+fn objects(dxf: &mut String, handles: &mut Handles) {
+    section(dxf, "OBJECTS");
+    let (root, groups) = (handles.next(), handles.next());
+    pairs(
+        dxf,
+        &[
+            (0, "DICTIONARY"),
+            (5, &root),
+            (330, "0"),
+            (100, "AcDbDictionary"),
+            (281, "1"),
+            (3, "ACAD_GROUP"),
+            (350, &groups),
+            (0, "DICTIONARY"),
+            (5, &groups),
+            (330, &root),
+            (100, "AcDbDictionary"),
+            (281, "1"),
+        ],
+    );
+    pairs(dxf, &[(0, "ENDSEC")]);
+}
+
+// Name of the layer of `color`, its hex code
+fn layer([r, g, b]: [u8; 3]) -> String {
+    format!("{:02X}{:02X}{:02X}", r, g, b)
+}
+
+// Handles of the objects of a drawing, given out from 1 in hex
+struct Handles(u32);
+
+impl Handles {
+    fn next(&mut self) -> String {
+        self.0 += 1;
+        format!("{:X}", self.0)
+    }
+    // the handle `next` gives out
+    fn peek(&self) -> String {
+        format!("{:X}", self.0 + 1)
+    }
+}
+
+// Start of the table `name` of `count` records, returns its handle
+fn table(dxf: &mut String, name: &str, handles: &mut Handles, count: usize) -> String {
+    let handle = handles.next();
+    pairs(
+        dxf,
+        &[
+            (0, "TABLE"),
+            (2, name),
+            (5, &handle),
+            (330, "0"),
+            (100, "AcDbSymbolTable"),
+            (70, &count.to_string()),
+        ],
+    );
+    handle
+}
+
+fn end_table(dxf: &mut String) {
+    pairs(dxf, &[(0, "ENDTAB")]);
+}
+
+// Start of a record of the table with the handle `table`, up to its flags
+fn record(
+    dxf: &mut String,
+    kind: &str,
+    handles: &mut Handles,
+    table: &str,
+    subclass: &str,
+    name: &str,
+) {
+    pairs(
+        dxf,
+        &[
+            (0, kind),
+            (5, &handles.next()),
+            (330, table),
+            (100, "AcDbSymbolTableRecord"),
+            (100, subclass),
+            (2, name),
+            (70, "0"),
+        ],
+    );
+}
+
+// Start of an entity of the block record `owner`, up to the group codes of
+// its `subclass`
+fn entity(
+    dxf: &mut String,
+    kind: &str,
+    handles: &mut Handles,
+    owner: &str,
+    layer: &str,
+    subclass: &str,
+) {
+    pairs(
+        dxf,
+        &[
+            (0, kind),
+            (5, &handles.next()),
+            (330, owner),
+            (100, "AcDbEntity"),
+            (8, layer),
+            (100, subclass),
+        ],
+    );
+}
@@ -0,0 +1,277 @@
+//! glTF 2.0, binary (GLB) or JSON with an embedded buffer.
+//!
+//! The mesh is a single primitive without indices: every face has its own
+//! three vertices so its color and normal stay flat.
+
+use super::{normal, save_with, upward};
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Width of the mesh in scene units, glTF units are meters.
+    pub width: f32,
+    /// Give the mesh this thickness in scene units, with a back and side
+    /// walls, instead of a single sided plane.
+    pub extrude: Option<f32>,
+    /// Show the colors as they are, without lighting, through the
+    /// `KHR_materials_unlit` extension.
+    pub unlit: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            width: 1.0,
+            extrude: None,
+            unlit: true,
+        }
+    }
+}
+
+/// Write `mesh` as GLB.
+///
+/// The mesh is centered on the origin in the xy plane, facing +z with y up,
+/// and faces are wound counter-clockwise seen from the front.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let buffer = buffer(mesh, options);
+    let mut json = document(mesh, options, &buffer, None)
+        .to_string()
+        .into_bytes();
+    // chunks are aligned to 4 bytes, JSON with spaces and binary with zeros
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    let mut bin = buffer.data;
+    while bin.len() % 4 != 0 {
+        bin.push(0);
+    }
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+    out.write_all(b"glTF")?;
+    out.write_all(&2u32.to_le_bytes())?;
+    out.write_all(&(length as u32).to_le_bytes())?;
+    out.write_all(&(json.len() as u32).to_le_bytes())?;
+    out.write_all(b"JSON")?;
+    out.write_all(&json)?;
+    out.write_all(&(bin.len() as u32).to_le_bytes())?;
+    out.write_all(b"BIN\0")?;
+    out.write_all(&bin)
+}
+
+/// Write `mesh` as glTF JSON with the buffer embedded as a data URI, see
+/// [`write`].
+pub fn write_json<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let buffer = buffer(mesh, options);
+    let uri = format!(
+        "data:application/octet-stream;base64,{}",
+        base64(&buffer.data)
+    );
+    let document = document(mesh, options, &buffer, Some(uri));
+    serde_json::to_writer(&mut *out, &document).map_err(io::Error::from)?;
+    writeln!(out)
+}
+
+/// Write `mesh` to `path`, as JSON when the extension is `.gltf` and as GLB
+/// otherwise.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    let json = path
+        .extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("gltf"));
+    validate(options).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| {
+        if json {
+            write_json(mesh, options, out)
+        } else {
+            write(mesh, options, out)
+        }
+    })
+}
+
+fn validate(options: &Options) -> io::Result<()> {
+    let positive = |v: f32| v > 0.0;
+    if !positive(options.width) || !options.extrude.is_none_or(positive) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the width and extrusion must be positive",
+        ));
+    }
+    Ok(())
+}
+
+// Positions, normals and colors one after the other, 3 floats per vertex each
+struct Buffer {
+    data: Vec<u8>,
+    vertices: usize,
+    min: [f32; 3],
+    max: [f32; 3],
+}
+
+fn buffer(mesh: &Mesh, options: &Options) -> Buffer {
+    let scale = options.width / mesh.width.max(1) as f32;
+    let point = |i: usize, z: f32| {
+        let (x, y) = mesh.points[i];
+        [
+            (x - mesh.width as f32 / 2.0) * scale,
+            (mesh.height as f32 / 2.0 - y) * scale,
+            z,
+        ]
+    };
+    let triangles = upward(mesh);
+
+    // flat triangles with the color of their face
+    let mut faces: Vec<([[f32; 3]; 3], [u8; 3])> = vec![];
+    for (&[a, b, c], color) in triangles.iter().zip(&mesh.colors) {
+        faces.push(([point(a, 0.0), point(b, 0.0), point(c, 0.0)], *color));
+    }
+    if let Some(depth) = options.extrude {
+        let back = -depth.abs();
+        for (&[a, b, c], color) in triangles.iter().zip(&mesh.colors) {
+            faces.push(([point(a, back), point(c, back), point(b, back)], *color));
+        }
+        // sides that only one face has are on the outline
+        let mut sides: HashMap<(usize, usize), usize> = HashMap::new();
+        for &[a, b, c] in &triangles {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                *sides.entry((u.min(v), u.max(v))).or_default() += 1;
+            }
+        }
+        for (&[a, b, c], color) in triangles.iter().zip(&mesh.colors) {
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                if sides[&(u.min(v), u.max(v))] == 1 {
+                    // the face is on the left of u to v, the wall faces right
+                    let (ut, ub) = (point(u, 0.0), point(u, back));
+                    let (vt, vb) = (point(v, 0.0), point(v, back));
+                    faces.push(([ut, ub, vb], *color));
+                    faces.push(([ut, vb, vt], *color));
+                }
+            }
+        }
+    }
+
+    let mut positions = vec![];
+    let mut normals = vec![];
+    let mut colors = vec![];
+    for (vertices, color) in &faces {
+        let normal = normal(vertices);
+        let color = color.map(linear);
+        for vertex in vertices {
+            positions.push(*vertex);
+            normals.push(normal);
+            colors.push(color);
+        }
+    }
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for position in &positions {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+    let mut data = Vec::with_capacity(positions.len() * 36);
+    for attribute in [&positions, &normals, &colors] {
+        for value in attribute.iter().flatten() {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    Buffer {
+        data,
+        vertices: positions.len(),
+        min,
+        max,
+    }
+}
+
+fn document(
+    mesh: &Mesh,
+    options: &Options,
+    buffer: &Buffer,
+    uri: Option<String>,
+) -> serde_json::Value {
+    let view = |n: usize| {
+        json!({
+            "buffer": 0,
+            "byteOffset": n * buffer.vertices * 12,
+            "byteLength": buffer.vertices * 12,
+            "target": 34962,
+        })
+    };
+    let mut material = json!({
+        "name": "faces",
+        "pbrMetallicRoughness": {
+            "baseColorFactor": [1.0, 1.0, 1.0, 1.0],
+            "metallicFactor": 0.0,
+            "roughnessFactor": 1.0,
+        },
+    });
+    let mut document = json!({
+        "asset": {"version": "2.0", "generator": "traingle"},
+        "scene": 0,
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"mesh": 0, "name": "traingle"}],
+        "meshes": [{
+            "name": format!("traingle {}x{}", mesh.width, mesh.height),
+            "primitives": [{
+                "attributes": {"POSITION": 0, "NORMAL": 1, "COLOR_0": 2},
+                "material": 0,
+                "mode": 4,
+            }],
+        }],
+        "buffers": [{"byteLength": buffer.data.len()}],
+        "bufferViews": [view(0), view(1), view(2)],
+        "accessors": [
+            {"bufferView": 0, "componentType": 5126, "count": buffer.vertices, "type": "VEC3",
+             "min": buffer.min, "max": buffer.max},
+            {"bufferView": 1, "componentType": 5126, "count": buffer.vertices, "type": "VEC3"},
+            {"bufferView": 2, "componentType": 5126, "count": buffer.vertices, "type": "VEC3"},
+        ],
+    });
+    if options.unlit {
+        material["extensions"] = json!({"KHR_materials_unlit": {}});
+        document["extensionsUsed"] = json!(["KHR_materials_unlit"]);
+    }
+    document["materials"] = json!([material]);
+    if let Some(uri) = uri {
+        document["buffers"][0]["uri"] = json!(uri);
+    }
+    document
+}
+
+// glTF vertex colors are linear, image colors sRGB
+fn linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
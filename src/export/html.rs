@@ -0,0 +1,270 @@
+//! Self-contained HTML viewer.
+//!
+//! The page embeds the meshes in the JSON mesh format and draws them on a
+//! canvas: hovering a face shows its color and fitness, a slider scrubs
+//! through the generations and toggles show the wireframe and vertices. It
+//! needs no server or network access.
+
+use super::save_with;
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Options of [`write`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Title of the page.
+    pub title: String,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            title: "traingle".to_string(),
+        }
+    }
+}
+
+/// Write a viewer of `meshes`, ordered by the generation recorded in them.
+pub fn write<W: Write>(meshes: &[Mesh], options: &Options, out: &mut W) -> io::Result<()> {
+    validate(meshes)?;
+    let mut meshes: Vec<&Mesh> = meshes.iter().collect();
+    meshes.sort_by_key(|mesh| mesh.run.as_ref().map(|run| run.generation));
+    // "</script" or "<!--" would end or break the script element the JSON is
+    // embedded in, and JSON has no "<" outside of strings
+    let data = serde_json::to_string(&meshes)
+        .map_err(io::Error::from)?
+        .replace('<', "\\u003c");
+    let title = escape(&options.title);
+    let page = fill(TEMPLATE, &[("{title}", &title), ("{meshes}", &data)]);
+    out.write_all(page.as_bytes())
+}
+
+/// Write a viewer of `meshes` to `path`, see [`write`].
+pub fn save(meshes: &[Mesh], options: &Options, path: &Path) -> Result<()> {
+    validate(meshes).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| write(meshes, options, out))
+}
+
+fn validate(meshes: &[Mesh]) -> io::Result<()> {
+    if meshes.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the viewer needs at least one mesh",
+        ));
+    }
+    Ok(())
+}
+
+// Replace the `placeholders` of `template` in a single pass, so a value that
+// contains a placeholder is kept as it is
+fn fill(template: &str, placeholders: &[(&str, &str)]) -> String {
+    let mut page = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some((at, (placeholder, value))) = placeholders
+        .iter()
+        .filter_map(|p| Some((rest.find(p.0)?, p)))
+        .min_by_key(|(at, _)| *at)
+    {
+        page.push_str(&rest[..at]);
+        page.push_str(value);
+        rest = &rest[at + placeholder.len()..];
+    }
+    page.push_str(rest);
+    page
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body { margin: 0; background: #1e1e1e; color: #ddd; font: 14px sans-serif; }
+main { max-width: 1000px; margin: 0 auto; padding: 16px; }
+h1 { font-size: 18px; font-weight: normal; }
+canvas { display: block; width: 100%; cursor: crosshair; }
+.controls { display: flex; flex-wrap: wrap; gap: 16px; align-items: center; margin: 12px 0; }
+.controls label:first-child { display: flex; flex: 1; gap: 8px; align-items: center; min-width: 240px; }
+#slider { flex: 1; }
+#info, #stats { font-family: monospace; min-height: 1.5em; }
+#swatch { display: inline-block; width: 1em; height: 1em; border: 1px solid #888; vertical-align: middle; }
+</style>
+</head>
+<body>
+<main>
+<h1>{title}</h1>
+<canvas id="canvas"></canvas>
+<div class="controls">
+<label>Generation <span id="generation"></span><input id="slider" type="range" min="0" step="1"></label>
+<label><input id="wireframe" type="checkbox"> Wireframe</label>
+<label><input id="vertices" type="checkbox"> Vertices</label>
+</div>
+<div id="stats"></div>
+<div id="info"></div>
+</main>
+<script id="meshes" type="application/json">{meshes}</script>
+<script>
+"use strict";
+const meshes = JSON.parse(document.getElementById("meshes").textContent);
+const canvas = document.getElementById("canvas");
+const context = canvas.getContext("2d");
+const slider = document.getElementById("slider");
+const wireframe = document.getElementById("wireframe");
+const vertices = document.getElementById("vertices");
+const info = document.getElementById("info");
+let current = meshes.length - 1;
+let hovered = -1;
+
+function hex(color) {
+  return "#" + color.map(c => c.toString(16).padStart(2, "0")).join("");
+}
+
+function generation(index) {
+  const run = meshes[index].run;
+  return run ? run.generation : index;
+}
+
+function corners(mesh, face) {
+  return mesh.triangles[face].map(i => mesh.points[i]);
+}
+
+function trace(mesh, face) {
+  const [a, b, c] = corners(mesh, face);
+  context.beginPath();
+  context.moveTo(a[0], a[1]);
+  context.lineTo(b[0], b[1]);
+  context.lineTo(c[0], c[1]);
+  context.closePath();
+}
+
+function resize() {
+  const mesh = meshes[current];
+  const ratio = window.devicePixelRatio || 1;
+  const width = canvas.clientWidth;
+  const height = width * mesh.height / mesh.width;
+  canvas.style.height = height + "px";
+  canvas.width = Math.max(1, Math.round(width * ratio));
+  canvas.height = Math.max(1, Math.round(height * ratio));
+  draw();
+}
+
+function draw() {
+  const mesh = meshes[current];
+  const scale = canvas.width / mesh.width;
+  const pixel = 1 / scale;
+  context.setTransform(scale, 0, 0, scale, 0, 0);
+  context.clearRect(0, 0, mesh.width, mesh.height);
+  mesh.triangles.forEach((_, face) => {
+    trace(mesh, face);
+    context.fillStyle = hex(mesh.colors[face]);
+    context.fill();
+    // a stroke of the same color hides the seams between faces
+    context.strokeStyle = context.fillStyle;
+    context.lineWidth = pixel;
+    context.stroke();
+  });
+  if (wireframe.checked) {
+    context.strokeStyle = "rgba(255, 255, 255, 0.6)";
+    context.lineWidth = pixel;
+    mesh.triangles.forEach((_, face) => {
+      trace(mesh, face);
+      context.stroke();
+    });
+  }
+  if (vertices.checked) {
+    context.fillStyle = "#ff4081";
+    for (const [x, y] of mesh.points) {
+      context.beginPath();
+      context.arc(x, y, 2.5 * pixel, 0, 2 * Math.PI);
+      context.fill();
+    }
+  }
+  if (hovered >= 0) {
+    trace(mesh, hovered);
+    context.strokeStyle = "#ffeb3b";
+    context.lineWidth = 2 * pixel;
+    context.stroke();
+  }
+}
+
+function describe() {
+  const mesh = meshes[current];
+  document.getElementById("generation").textContent =
+    generation(current) + " (" + (current + 1) + " of " + meshes.length + ")";
+  let stats = mesh.width + "x" + mesh.height + ", " + mesh.points.length + " points, " +
+    mesh.triangles.length + " faces";
+  if (mesh.fitness && mesh.fitness.length) {
+    const total = mesh.fitness.reduce((sum, f) => sum + f, 0);
+    stats += ", average face fitness " + (total / mesh.fitness.length).toFixed(3);
+  }
+  document.getElementById("stats").textContent = stats;
+  if (hovered < 0) {
+    info.textContent = "Hover a face to see its color and fitness.";
+    return;
+  }
+  const color = mesh.colors[hovered];
+  const fitness = mesh.fitness && mesh.fitness.length ? mesh.fitness[hovered].toFixed(3) : "unknown";
+  info.innerHTML = "";
+  const swatch = document.createElement("span");
+  swatch.id = "swatch";
+  swatch.style.background = hex(color);
+  info.append(swatch, " face " + hovered + ": " + hex(color) + " rgb(" + color.join(", ") +
+    "), fitness " + fitness);
+}
+
+function contains([a, b, c], [x, y]) {
+  const side = (u, v) => (v[0] - u[0]) * (y - u[1]) - (v[1] - u[1]) * (x - u[0]);
+  const d = [side(a, b), side(b, c), side(c, a)];
+  return !(d.some(s => s < 0) && d.some(s => s > 0));
+}
+
+function hover(event) {
+  const mesh = meshes[current];
+  const rect = canvas.getBoundingClientRect();
+  const point = [
+    (event.clientX - rect.left) / rect.width * mesh.width,
+    (event.clientY - rect.top) / rect.height * mesh.height,
+  ];
+  const face = mesh.triangles.findIndex((_, face) => contains(corners(mesh, face), point));
+  if (face !== hovered) {
+    hovered = face;
+    draw();
+    describe();
+  }
+}
+
+slider.max = meshes.length - 1;
+slider.value = current;
+slider.disabled = meshes.length < 2;
+slider.addEventListener("input", () => {
+  current = Number(slider.value);
+  hovered = -1;
+  resize();
+  describe();
+});
+wireframe.addEventListener("change", draw);
+vertices.addEventListener("change", draw);
+canvas.addEventListener("mousemove", hover);
+canvas.addEventListener("mouseleave", () => {
+  hovered = -1;
+  draw();
+  describe();
+});
+window.addEventListener("resize", resize);
+resize();
+describe();
+</script>
+</body>
+</html>
+"##;
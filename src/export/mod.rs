@@ -0,0 +1,117 @@
+//! Vector and 3D formats for evolved meshes.
+//!
+//! Every format has a module with its `Options`, a `write` function for any
+//! [`std::io::Write`] and a `save` function writing to a file.
+
+pub mod dxf;
+pub mod gltf;
+pub mod html;
+pub mod numbers;
+pub mod obj;
+pub mod pdf;
+pub mod plotter;
+pub mod ply;
+pub mod stl;
+pub mod svg;
+
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+// Create `path` and hand a buffered writer to `write`, the file is removed
+// again when writing fails. Options are checked before, rejecting them must
+// not truncate the file.
+pub(crate) fn save_with<F>(path: &Path, write: F) -> Result<()>
+where
+    F: FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+{
+    let file = File::create(path).map_err(|e| Error::io(path, e))?;
+    let mut out = BufWriter::new(file);
+    let written = write(&mut out).and_then(|_| out.flush());
+    if written.is_err() {
+        drop(out);
+        let _ = std::fs::remove_file(path);
+    }
+    written.map_err(|e| Error::io(path, e))
+}
+
+// Shortest decimal of `x` rounded to 2 digits, coordinates don't need more
+pub(crate) fn number(x: f32) -> String {
+    let s = format!("{:.2}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+pub(crate) fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Relative luminance of a color, 0 to 1
+pub(crate) fn luminance([r, g, b]: [u8; 3]) -> f32 {
+    (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32) / 255.0
+}
+
+// Points in 3D with y pointing up, so the image isn't mirrored, and z the
+// average luminance of the faces around each point times `height`
+pub(crate) fn vertices(mesh: &Mesh, height: Option<f32>) -> Vec<[f32; 3]> {
+    let mut z = vec![(0.0, 0); mesh.points.len()];
+    if height.is_some() {
+        for (triangle, color) in mesh.triangles.iter().zip(&mesh.colors) {
+            for i in triangle {
+                z[*i].0 += luminance(*color);
+                z[*i].1 += 1;
+            }
+        }
+    }
+    let height_of = |(sum, n): (f32, u32)| match height {
+        Some(height) if n > 0 => sum / n as f32 * height,
+        _ => 0.0,
+    };
+    mesh.points
+        .iter()
+        .zip(z)
+        .map(|((x, y), z)| [*x, mesh.height as f32 - y, height_of(z)])
+        .collect()
+}
+
+// Unit normal of a triangle wound counter-clockwise around it
+pub(crate) fn normal([a, b, c]: &[[f32; 3]; 3]) -> [f32; 3] {
+    let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+    let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+    let n = [
+        u[1] * v[2] - u[2] * v[1],
+        u[2] * v[0] - u[0] * v[2],
+        u[0] * v[1] - u[1] * v[0],
+    ];
+    let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+    if length > 0.0 {
+        n.map(|c| c / length)
+    } else {
+        // a face without area, any unit normal is as good
+        [0.0, 0.0, 1.0]
+    }
+}
+
+// Triangles wound counter-clockwise seen from +z, once y points up
+pub(crate) fn upward(mesh: &Mesh) -> Vec<[usize; 3]> {
+    mesh.triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let (pa, pb, pc) = (mesh.points[a], mesh.points[b], mesh.points[c]);
+            // in image coordinates y points down, which mirrors the winding
+            let cross = (pb.0 - pa.0) * (pc.1 - pa.1) - (pb.1 - pa.1) * (pc.0 - pa.0);
+            if cross > 0.0 {
+                [a, c, b]
+            } else {
+                [a, b, c]
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,465 @@
+//! Paint-by-numbers kits.
+//!
+//! The colors of a mesh are reduced to a palette, numbered from light to
+//! dark, and adjacent faces of the same color merged into regions. The sheet
+//! shows the outline of every region with its number at the point deepest
+//! inside it, the legend a swatch of every number. Both are written as SVG
+//! and rendered as images.
+
+use super::{hex, luminance, number, save_with};
+use crate::error::Result;
+use crate::geom::contains;
+use crate::mesh::Mesh;
+use crate::palette;
+use crate::region::{self, Region};
+
+use image::{Rgb, RgbImage};
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+// Color of the outlines and numbers
+const INK: [u8; 3] = [0x55, 0x55, 0x55];
+// Layout of the legend: height of a row, size of a swatch, x of the number
+// and of the color code, and the width of the sheet
+const ROW: u32 = 48;
+const SWATCH: u32 = 36;
+const NUMBER_X: u32 = 60;
+const CODE_X: u32 = 110;
+const LEGEND_WIDTH: u32 = 260;
+// Height of the legend text, five rows of the font at 4 units
+const LEGEND_TEXT: u32 = 20;
+
+/// Options of [`Kit::new`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Number of paints, the colors are reduced to at most this many.
+    pub colors: usize,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options { colors: 12 }
+    }
+}
+
+/// A paint-by-numbers kit of a mesh.
+pub struct Kit {
+    // the mesh with its colors reduced to the palette
+    mesh: Mesh,
+    // paints, numbered from 1
+    palette: Vec<[u8; 3]>,
+    regions: Vec<Region>,
+    labels: Vec<Label>,
+}
+
+// Number of a region, where it's written and its height
+struct Label {
+    number: usize,
+    position: (f32, f32),
+    size: f32,
+}
+
+impl Kit {
+    /// Reduce the colors of `mesh` and number its regions.
+    pub fn new(mesh: &Mesh, options: &Options) -> Kit {
+        let (mesh, mut palette) = palette::quantize(mesh, options.colors.max(1));
+        // k-means can end with two centers rounded to the same color, which
+        // would be two paints for one region color
+        palette.sort_unstable();
+        palette.dedup();
+        palette.retain(|color| mesh.colors.contains(color));
+        palette.sort_by(|a, b| luminance(*b).total_cmp(&luminance(*a)));
+        let numbers: HashMap<[u8; 3], usize> = palette
+            .iter()
+            .enumerate()
+            .map(|(i, color)| (*color, i + 1))
+            .collect();
+
+        let regions = region::regions(&mesh);
+        let longest = mesh.width.max(mesh.height) as f32;
+        let labels = regions
+            .iter()
+            .map(|region| {
+                let number = numbers[&region.color];
+                let (position, clearance) = deepest(&mesh, region);
+                // as large as fits the clearance, digits being 0.6 as wide as high
+                let digits = number.to_string().len() as f32;
+                let size = 2.0 * clearance / (1.0 + (0.6 * digits).powi(2)).sqrt();
+                Label {
+                    number,
+                    position,
+                    size: size.clamp(longest / 150.0, longest / 20.0),
+                }
+            })
+            .collect();
+        Kit {
+            mesh,
+            palette,
+            regions,
+            labels,
+        }
+    }
+    /// Paints of the kit, lightest first, numbered from 1.
+    pub fn palette(&self) -> &[[u8; 3]] {
+        &self.palette
+    }
+    /// Number of regions to paint.
+    pub fn regions(&self) -> usize {
+        self.regions.len()
+    }
+    /// Write the sheet to paint as SVG, the viewBox matches the image the
+    /// mesh was evolved on.
+    pub fn write_sheet<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mesh = &self.mesh;
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = mesh.width,
+            h = mesh.height
+        )?;
+        writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+        writeln!(
+            out,
+            r#"<g fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round">"#,
+            hex(INK),
+            number(self.line_width())
+        )?;
+        for region in &self.regions {
+            let mut d = String::new();
+            for outline in &region.outlines {
+                for (n, i) in outline.iter().enumerate() {
+                    let (x, y) = mesh.points[*i];
+                    let command = if n == 0 { 'M' } else { 'L' };
+                    d.push_str(&format!("{}{} {}", command, number(x), number(y)));
+                }
+                d.push('Z');
+            }
+            writeln!(out, r#"<path d="{}"/>"#, d)?;
+        }
+        writeln!(out, "</g>")?;
+        writeln!(
+            out,
+            r#"<g fill="{}" font-family="sans-serif" text-anchor="middle" dominant-baseline="central">"#,
+            hex(INK)
+        )?;
+        for label in &self.labels {
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}" font-size="{}">{}</text>"#,
+                number(label.position.0),
+                number(label.position.1),
+                number(label.size),
+                label.number
+            )?;
+        }
+        writeln!(out, "</g>")?;
+        writeln!(out, "</svg>")
+    }
+    /// Write the legend as SVG, a swatch, number and color code per paint.
+    pub fn write_legend<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let height = self.legend_height();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = LEGEND_WIDTH,
+            h = height
+        )?;
+        writeln!(out, r#"<rect width="100%" height="100%" fill="white"/>"#)?;
+        writeln!(
+            out,
+            r#"<g font-family="sans-serif" font-size="{}" fill="{}" dominant-baseline="central">"#,
+            LEGEND_TEXT,
+            hex(INK)
+        )?;
+        for (i, color) in self.palette.iter().enumerate() {
+            let top = (ROW - SWATCH) / 2 + ROW * i as u32;
+            let middle = top + SWATCH / 2;
+            writeln!(
+                out,
+                r#"<rect x="{x}" y="{y}" width="{s}" height="{s}" fill="{c}" stroke="{ink}"/>"#,
+                x = (ROW - SWATCH) / 2,
+                y = top,
+                s = SWATCH,
+                c = hex(*color),
+                ink = hex(INK)
+            )?;
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                NUMBER_X,
+                middle,
+                i + 1
+            )?;
+            writeln!(
+                out,
+                r#"<text x="{}" y="{}">{}</text>"#,
+                CODE_X,
+                middle,
+                hex(*color)
+            )?;
+        }
+        writeln!(out, "</g>")?;
+        writeln!(out, "</svg>")
+    }
+    /// Write the sheet as SVG to `path`, see [`Kit::write_sheet`].
+    pub fn save_sheet(&self, path: &Path) -> Result<()> {
+        save_with(path, |out| self.write_sheet(out))
+    }
+    /// Write the legend as SVG to `path`, see [`Kit::write_legend`].
+    pub fn save_legend(&self, path: &Path) -> Result<()> {
+        save_with(path, |out| self.write_legend(out))
+    }
+    /// Render the sheet into an image `width` pixels wide.
+    pub fn render_sheet(&self, width: u32) -> RgbImage {
+        let (size, scale) = self.size(width);
+        let (width, height) = size;
+        let coverage = self.coverage(size);
+        let at = |x: u32, y: u32| coverage[(y * width + x) as usize];
+
+        // lines where the region changes, and around the sheet
+        let mut sheet = RgbImage::from_pixel(width, height, Rgb([255, 255, 255]));
+        let thickness = (self.line_width() * scale / 2.0).round() as i64;
+        for y in 0..height {
+            for x in 0..width {
+                let edge = x == 0
+                    || y == 0
+                    || x + 1 == width
+                    || y + 1 == height
+                    || at(x + 1, y) != at(x, y)
+                    || at(x, y + 1) != at(x, y);
+                if edge {
+                    fill(
+                        &mut sheet,
+                        (x as i64 - thickness, y as i64 - thickness),
+                        (2 * thickness + 1, 2 * thickness + 1),
+                        INK,
+                    );
+                }
+            }
+        }
+        for label in &self.labels {
+            let text = label.number.to_string();
+            let cell = ((label.size * scale / 5.0).round() as i64).max(1);
+            let (w, h) = text_size(&text, cell);
+            let (x, y) = (label.position.0 * scale, label.position.1 * scale);
+            draw_text(
+                &mut sheet,
+                &text,
+                (x.round() as i64 - w / 2, y.round() as i64 - h / 2),
+                cell,
+            );
+        }
+        sheet
+    }
+    /// Render the finished painting, the mesh in the paints of the kit, into
+    /// an image `width` pixels wide.
+    pub fn render_painting(&self, width: u32) -> RgbImage {
+        let ((width, height), _) = self.size(width);
+        let coverage = self.coverage((width, height));
+        RgbImage::from_fn(width, height, |x, y| {
+            match self.regions.get(coverage[(y * width + x) as usize]) {
+                Some(region) => Rgb(region.color),
+                None => Rgb([255, 255, 255]),
+            }
+        })
+    }
+    /// Render the legend at the largest whole multiple of its SVG size that
+    /// fits in `width` pixels, at least once.
+    pub fn render_legend(&self, width: u32) -> RgbImage {
+        let scale = (width / LEGEND_WIDTH).max(1);
+        let mut legend = RgbImage::from_pixel(
+            LEGEND_WIDTH * scale,
+            self.legend_height() * scale,
+            Rgb([255, 255, 255]),
+        );
+        let s = scale as i64;
+        let cell = LEGEND_TEXT as i64 / 5 * s;
+        for (i, color) in self.palette.iter().enumerate() {
+            let top = ((ROW - SWATCH) / 2 + ROW * i as u32) as i64 * s;
+            let left = ((ROW - SWATCH) / 2) as i64 * s;
+            let side = SWATCH as i64 * s;
+            fill(&mut legend, (left, top), (side, side), INK);
+            fill(
+                &mut legend,
+                (left + s, top + s),
+                (side - 2 * s, side - 2 * s),
+                *color,
+            );
+            let middle = top + side / 2;
+            let number = (i + 1).to_string();
+            let (_, h) = text_size(&number, cell);
+            draw_text(
+                &mut legend,
+                &number,
+                (NUMBER_X as i64 * s, middle - h / 2),
+                cell,
+            );
+            // the font has no #
+            draw_text(
+                &mut legend,
+                &hex(*color)[1..],
+                (CODE_X as i64 * s, middle - h / 2),
+                cell,
+            );
+        }
+        legend
+    }
+    // Size of an image `width` pixels wide and its scale from the mesh
+    fn size(&self, width: u32) -> ((u32, u32), f32) {
+        let width = width.max(1);
+        let scale = width as f32 / self.mesh.width as f32;
+        let height = ((self.mesh.height as f32 * scale).round() as u32).max(1);
+        ((width, height), scale)
+    }
+    // Region of every pixel of an image of `size`, the one covering its
+    // center, or past the last region for none
+    fn coverage(&self, (width, height): (u32, u32)) -> Vec<usize> {
+        let mesh = &self.mesh;
+        let scale = (
+            width as f32 / mesh.width as f32,
+            height as f32 / mesh.height as f32,
+        );
+        let mut coverage = vec![self.regions.len(); (width * height) as usize];
+        for (r, region) in self.regions.iter().enumerate() {
+            for face in &region.faces {
+                let [a, b, c] = mesh.triangles[*face].map(|i| {
+                    let (x, y) = mesh.points[i];
+                    (x * scale.0, y * scale.1)
+                });
+                let pixels = |low: f32, high: f32, size: u32| {
+                    let first = (low - 0.5).ceil().max(0.0) as u32;
+                    let last = ((high - 0.5).floor() + 1.0).clamp(0.0, size as f32) as u32;
+                    first..last
+                };
+                let xs = pixels(a.0.min(b.0).min(c.0), a.0.max(b.0).max(c.0), width);
+                for y in pixels(a.1.min(b.1).min(c.1), a.1.max(b.1).max(c.1), height) {
+                    for x in xs.clone() {
+                        if contains([a, b, c], (x as f32 + 0.5, y as f32 + 0.5)) {
+                            coverage[(y * width + x) as usize] = r;
+                        }
+                    }
+                }
+            }
+        }
+        coverage
+    }
+    fn legend_height(&self) -> u32 {
+        (ROW * self.palette.len() as u32).max(ROW)
+    }
+    // Width of the outlines in the units of the mesh
+    fn line_width(&self) -> f32 {
+        self.mesh.width.max(self.mesh.height) as f32 / 800.0
+    }
+}
+
+// Point of a region furthest from its outlines and that distance, picked
+// among the incenters of its faces
+fn deepest(mesh: &Mesh, region: &Region) -> ((f32, f32), f32) {
+    let sides: Vec<((f32, f32), (f32, f32))> = region
+        .outlines
+        .iter()
+        .flat_map(|outline| {
+            outline.iter().enumerate().map(move |(n, i)| {
+                let j = outline[(n + 1) % outline.len()];
+                (mesh.points[*i], mesh.points[j])
+            })
+        })
+        .collect();
+    let mut best = ((0.0, 0.0), f32::NEG_INFINITY);
+    for face in &region.faces {
+        let [a, b, c] = mesh.triangles[*face].map(|i| mesh.points[i]);
+        let (la, lb, lc) = (length(b, c), length(c, a), length(a, b));
+        let perimeter = la + lb + lc;
+        let point = if perimeter > 0.0 {
+            (
+                (la * a.0 + lb * b.0 + lc * c.0) / perimeter,
+                (la * a.1 + lb * b.1 + lc * c.1) / perimeter,
+            )
+        } else {
+            a
+        };
+        let clearance = sides
+            .iter()
+            .map(|(p, q)| to_segment(point, *p, *q))
+            .fold(f32::INFINITY, f32::min);
+        if clearance > best.1 {
+            best = (point, clearance);
+        }
+    }
+    best
+}
+
+fn length(a: (f32, f32), b: (f32, f32)) -> f32 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+// Distance from `point` to the segment from `a` to `b`
+fn to_segment(point: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let squared = dx * dx + dy * dy;
+    let t = if squared > 0.0 {
+        (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    length(point, (a.0 + t * dx, a.1 + t * dy))
+}
+
+// Fill a rectangle, clipped to the image
+fn fill(image: &mut RgbImage, (x, y): (i64, i64), (w, h): (i64, i64), color: [u8; 3]) {
+    let (width, height) = (image.width() as i64, image.height() as i64);
+    for py in y.max(0)..(y + h).min(height) {
+        for px in x.max(0)..(x + w).min(width) {
+            image.put_pixel(px as u32, py as u32, Rgb(color));
+        }
+    }
+}
+
+// Rows of the 3x5 font, the high bit on the left
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'a' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'b' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'c' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'd' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'e' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'f' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        _ => [0; 5],
+    }
+}
+
+// Size of `text` with font pixels of `cell` pixels, a blank column between
+// characters
+fn text_size(text: &str, cell: i64) -> (i64, i64) {
+    let n = text.chars().count() as i64;
+    ((4 * n - 1).max(0) * cell, 5 * cell)
+}
+
+fn draw_text(image: &mut RgbImage, text: &str, (x, y): (i64, i64), cell: i64) {
+    for (n, c) in text.chars().enumerate() {
+        let left = x + 4 * cell * n as i64;
+        for (row, bits) in glyph(c).iter().enumerate() {
+            for column in 0..3 {
+                if bits & (0b100 >> column) != 0 {
+                    fill(
+                        image,
+                        (left + column * cell, y + row as i64 * cell),
+                        (cell, cell),
+                        INK,
+                    );
+                }
+            }
+        }
+    }
+}
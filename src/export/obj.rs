@@ -0,0 +1,130 @@
+//! Wavefront OBJ, colored with a material per color or with vertex colors.
+
+use super::{hex, number, save_with, upward, vertices};
+use crate::error::Result;
+use crate::mesh::Mesh;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// How faces get their color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colors {
+    /// A material per color in a separate MTL file.
+    Materials,
+    /// `v x y z r g b` vertices, duplicated for every face so faces stay
+    /// flat.
+    Vertex,
+}
+
+/// Options of [`write`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub colors: Colors,
+    /// Lift every point by the average luminance of its faces times
+    /// `height`, in pixels. The mesh stays in the z=0 plane when `None`.
+    pub height: Option<f32>,
+    /// MTL file the OBJ refers to with [`Colors::Materials`], [`save`]
+    /// names it after the OBJ.
+    pub material_library: String,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            colors: Colors::Materials,
+            height: None,
+            material_library: "mesh.mtl".to_string(),
+        }
+    }
+}
+
+/// Write `mesh` as OBJ in pixel units, x to the right, y up and z towards
+/// the viewer. Faces are wound counter-clockwise seen from above.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    let vertices = vertices(mesh, options.height);
+    let triangles = upward(mesh);
+    writeln!(out, "# traingle mesh, {}x{}", mesh.width, mesh.height)?;
+    match options.colors {
+        Colors::Materials => {
+            writeln!(out, "mtllib {}", options.material_library)?;
+            for [x, y, z] in &vertices {
+                writeln!(out, "v {} {} {}", number(*x), number(*y), number(*z))?;
+            }
+            // faces of one color together, to switch materials once per color
+            let mut groups: BTreeMap<[u8; 3], Vec<&[usize; 3]>> = BTreeMap::new();
+            for (triangle, color) in triangles.iter().zip(&mesh.colors) {
+                groups.entry(*color).or_default().push(triangle);
+            }
+            for (color, triangles) in groups {
+                writeln!(out, "usemtl {}", material(color))?;
+                for [a, b, c] in triangles {
+                    // OBJ indices start at 1
+                    writeln!(out, "f {} {} {}", a + 1, b + 1, c + 1)?;
+                }
+            }
+        }
+        Colors::Vertex => {
+            for (triangle, color) in triangles.iter().zip(&mesh.colors) {
+                let [r, g, b] = color.map(|c| c as f32 / 255.0);
+                for i in triangle {
+                    let [x, y, z] = vertices[*i];
+                    writeln!(
+                        out,
+                        "v {} {} {} {:.4} {:.4} {:.4}",
+                        number(x),
+                        number(y),
+                        number(z),
+                        r,
+                        g,
+                        b
+                    )?;
+                }
+            }
+            for face in 0..triangles.len() {
+                let first = face * 3 + 1;
+                writeln!(out, "f {} {} {}", first, first + 1, first + 2)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write the materials [`write`] refers to, one per color of `mesh`.
+pub fn write_materials<W: Write>(mesh: &Mesh, out: &mut W) -> io::Result<()> {
+    let mut colors = mesh.colors.clone();
+    colors.sort_unstable();
+    colors.dedup();
+    for color in colors {
+        let [r, g, b] = color.map(|c| c as f32 / 255.0);
+        writeln!(out, "newmtl {}", material(color))?;
+        writeln!(out, "Kd {:.4} {:.4} {:.4}", r, g, b)?;
+        // flat color, no lighting highlights
+        writeln!(out, "illum 1")?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write `mesh` as OBJ to `path`, with [`Colors::Materials`] the MTL file is
+/// written next to it.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    if options.colors == Colors::Vertex {
+        return save_with(path, |out| write(mesh, options, out));
+    }
+    let library = path.with_extension("mtl");
+    let options = Options {
+        material_library: library
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| options.material_library.clone()),
+        ..options.clone()
+    };
+    save_with(path, |out| write(mesh, &options, out))?;
+    save_with(&library, |out| write_materials(mesh, out))
+}
+
+fn material(color: [u8; 3]) -> String {
+    format!("color_{}", &hex(color)[1..])
+}
@@ -0,0 +1,271 @@
+//! Single page PDF with one filled path per face, written without
+//! dependencies.
+
+use super::{number, save_with};
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+use crate::region;
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+// PDF user space unit is 1/72 inch
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+/// Trimmed page size in millimetres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSize {
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Size of the page after trimming, A4 portrait by default.
+    pub page: PageSize,
+    /// Blank space between the mesh and the trimmed edge, in millimetres.
+    pub margin: f32,
+    /// Extra space around the page the mesh extends into, cut off after
+    /// printing, in millimetres.
+    pub bleed: f32,
+    /// Scale the mesh to cover the printable area and crop it, instead of
+    /// fitting it inside.
+    pub cover: bool,
+    /// Merge adjacent faces of the same color into a single path.
+    pub merge: bool,
+}
+
+/// Write `mesh` as a PDF page, scaled to the page whatever the size of the
+/// image it was evolved on.
+///
+/// The mesh is centered inside the page less the margins. With a bleed the
+/// media box grows by it on every side and the trim box marks the page. A
+/// mesh without margins extends into the bleed so that it reaches past the
+/// cut.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let bleed = options.bleed * POINTS_PER_MM;
+    let margin = options.margin * POINTS_PER_MM;
+    let page_w = options.page.width * POINTS_PER_MM;
+    let page_h = options.page.height * POINTS_PER_MM;
+    let media = (page_w + 2.0 * bleed, page_h + 2.0 * bleed);
+
+    // printable area from the bottom left of the media box, the trimmed page
+    // inset by the margins, or the whole media box when there are none
+    let (area_x, area_y, area_w, area_h) = if margin > 0.0 {
+        let inset = bleed + margin;
+        (inset, inset, page_w - 2.0 * margin, page_h - 2.0 * margin)
+    } else {
+        (0.0, 0.0, media.0, media.1)
+    };
+    let (mesh_w, mesh_h) = mesh.dimensions();
+    let scale = if options.cover {
+        f32::max(area_w / mesh_w, area_h / mesh_h)
+    } else {
+        f32::min(area_w / mesh_w, area_h / mesh_h)
+    };
+    let x0 = area_x + (area_w - mesh_w * scale) / 2.0;
+    let y0 = area_y + (area_h - mesh_h * scale) / 2.0;
+    // PDF y grows upwards, image y downwards
+    let point = |i: usize| {
+        let (x, y) = mesh.points[i];
+        (x0 + x * scale, y0 + (mesh_h - y) * scale)
+    };
+
+    let mut content = String::new();
+    // points outside the image are cropped like in a render
+    let clip = if options.cover {
+        (area_x, area_y, area_w, area_h)
+    } else {
+        (x0, y0, mesh_w * scale, mesh_h * scale)
+    };
+    let _ = writeln!(
+        content,
+        "{} {} {} {} re W n",
+        number(clip.0),
+        number(clip.1),
+        number(clip.2),
+        number(clip.3)
+    );
+    let mut current = None;
+    let mut fill = |content: &mut String, color: [u8; 3]| {
+        if current != Some(color) {
+            let [r, g, b] = color.map(|c| c as f32 / 255.0);
+            let _ = writeln!(content, "{:.3} {:.3} {:.3} rg", r, g, b);
+            current = Some(color);
+        }
+    };
+    if options.merge {
+        for region in region::regions(mesh) {
+            fill(&mut content, region.color);
+            for outline in &region.outlines {
+                for (n, i) in outline.iter().enumerate() {
+                    let (x, y) = point(*i);
+                    let operator = if n == 0 { "m" } else { "l" };
+                    let _ = write!(content, "{} {} {} ", number(x), number(y), operator);
+                }
+                content.push_str("h ");
+            }
+            content.push_str("f\n");
+        }
+    } else {
+        for (triangle, color) in mesh.triangles.iter().zip(&mesh.colors) {
+            fill(&mut content, *color);
+            let [a, b, c] = triangle.map(point);
+            let _ = writeln!(
+                content,
+                "{} {} m {} {} l {} {} l h f",
+                number(a.0),
+                number(a.1),
+                number(b.0),
+                number(b.1),
+                number(c.0),
+                number(c.1)
+            );
+        }
+    }
+
+    let mut page = format!(
+        "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Contents 4 0 R /Resources << >>",
+        number(media.0),
+        number(media.1)
+    );
+    if bleed > 0.0 {
+        let _ = write!(
+            page,
+            " /BleedBox [0 0 {} {}] /TrimBox [{b} {b} {} {}]",
+            number(media.0),
+            number(media.1),
+            number(bleed + page_w),
+            number(bleed + page_h),
+            b = number(bleed)
+        );
+    }
+    page.push_str(" >>");
+    let objects = [
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+        page,
+        format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ),
+    ];
+
+    let mut pdf: Vec<u8> = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (n, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n{}\nendobj\n", n + 1, object).as_bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend_from_slice(
+        format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes(),
+    );
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref
+        )
+        .as_bytes(),
+    );
+    out.write_all(&pdf)
+}
+
+/// Write `mesh` as a PDF page to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    validate(options).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| write(mesh, options, out))
+}
+
+fn validate(options: &Options) -> io::Result<()> {
+    if !(options.margin >= 0.0 && options.bleed >= 0.0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "margin and bleed must not be negative",
+        ));
+    }
+    let room = |side: f32| side - 2.0 * options.margin > 0.0;
+    if !(room(options.page.width) && room(options.page.height)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the margins leave no room on the page",
+        ));
+    }
+    Ok(())
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            page: PageSize::A4,
+            margin: 10.0,
+            bleed: 0.0,
+            cover: false,
+            merge: false,
+        }
+    }
+}
+
+impl PageSize {
+    pub const A5: PageSize = PageSize::new(148.0, 210.0);
+    pub const A4: PageSize = PageSize::new(210.0, 297.0);
+    pub const A3: PageSize = PageSize::new(297.0, 420.0);
+    pub const A2: PageSize = PageSize::new(420.0, 594.0);
+    pub const A1: PageSize = PageSize::new(594.0, 841.0);
+    pub const A0: PageSize = PageSize::new(841.0, 1189.0);
+    pub const LETTER: PageSize = PageSize::new(215.9, 279.4);
+    pub const LEGAL: PageSize = PageSize::new(215.9, 355.6);
+    pub const TABLOID: PageSize = PageSize::new(279.4, 431.8);
+
+    pub const fn new(width: f32, height: f32) -> PageSize {
+        PageSize { width, height }
+    }
+    /// The same page turned sideways.
+    pub fn landscape(self) -> PageSize {
+        PageSize::new(self.height, self.width)
+    }
+}
+
+/// Parses a paper name (`a0` to `a5`, `letter`, `legal`, `tabloid`) or
+/// `<width>x<height>` in millimetres.
+impl FromStr for PageSize {
+    type Err = String;
+    fn from_str(s: &str) -> std::result::Result<PageSize, String> {
+        let size = match s.to_lowercase().as_str() {
+            "a0" => PageSize::A0,
+            "a1" => PageSize::A1,
+            "a2" => PageSize::A2,
+            "a3" => PageSize::A3,
+            "a4" => PageSize::A4,
+            "a5" => PageSize::A5,
+            "letter" => PageSize::LETTER,
+            "legal" => PageSize::LEGAL,
+            "tabloid" => PageSize::TABLOID,
+            other => {
+                let error = || {
+                    format!(
+                        "unknown page size {}, expected a0 to a5, letter, legal, tabloid or <width>x<height> in mm",
+                        s
+                    )
+                };
+                let (w, h) = other.split_once('x').ok_or_else(error)?;
+                let w: f32 = w.trim().parse().map_err(|_| error())?;
+                let h: f32 = h.trim().parse().map_err(|_| error())?;
+                if !(w > 0.0 && h > 0.0 && w.is_finite() && h.is_finite()) {
+                    return Err(error());
+                }
+                PageSize::new(w, h)
+            }
+        };
+        Ok(size)
+    }
+}
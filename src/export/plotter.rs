@@ -0,0 +1,221 @@
+//! Layered SVG for pen plotters and vinyl cutters.
+//!
+//! Every color is an Inkscape layer with the outlines of the regions of that
+//! color, adjacent faces merged, and optionally hatch lines filling them.
+//! Coordinates are in millimetres and the paths of each layer are ordered to
+//! keep the pen-up travel between them short.
+
+use super::{hex, luminance, number, save_with};
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+use crate::palette;
+use crate::region;
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    /// Reduce the colors to a palette of at most this many colors first,
+    /// one layer each.
+    pub colors: Option<usize>,
+    /// Width of the drawing in mm.
+    pub width: f32,
+    /// Width of the pen in mm, the stroke width.
+    pub pen: f32,
+    /// Fill regions with parallel lines this many mm apart.
+    pub hatch: Option<f32>,
+    /// Angle of the hatch lines in degrees.
+    pub hatch_angle: f32,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            colors: None,
+            width: 200.0,
+            pen: 0.3,
+            hatch: None,
+            hatch_angle: 45.0,
+        }
+    }
+}
+
+type Point = (f32, f32);
+
+// A path the pen draws without lifting
+enum Stroke {
+    // closed, it can start at any of its points
+    Loop(Vec<Point>),
+    // it can be drawn either way
+    Line(Point, Point),
+}
+
+/// Write `mesh` as a layered SVG, lightest color first.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let quantized;
+    let mesh = match options.colors {
+        Some(k) => {
+            quantized = palette::quantize(mesh, k).0;
+            &quantized
+        }
+        None => mesh,
+    };
+    let scale = options.width / mesh.width as f32;
+    let point = |i: &usize| (mesh.points[*i].0 * scale, mesh.points[*i].1 * scale);
+
+    let mut layers: Vec<([u8; 3], Vec<Stroke>)> = vec![];
+    let mut layer_of: HashMap<[u8; 3], usize> = HashMap::new();
+    for region in region::regions(mesh) {
+        let layer = *layer_of.entry(region.color).or_insert_with(|| {
+            layers.push((region.color, vec![]));
+            layers.len() - 1
+        });
+        let outlines: Vec<Vec<Point>> = region
+            .outlines
+            .iter()
+            .map(|outline| outline.iter().map(point).collect())
+            .collect();
+        if let Some(spacing) = options.hatch {
+            for (a, b) in hatch(&outlines, spacing, options.hatch_angle) {
+                layers[layer].1.push(Stroke::Line(a, b));
+            }
+        }
+        layers[layer]
+            .1
+            .extend(outlines.into_iter().map(Stroke::Loop));
+    }
+    layers.sort_by(|(a, _), (b, _)| luminance(*b).total_cmp(&luminance(*a)));
+
+    let (width, height) = (options.width, mesh.height as f32 * scale);
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape" width="{w}mm" height="{h}mm" viewBox="0 0 {w} {h}">"#,
+        w = number(width),
+        h = number(height)
+    )?;
+    for (n, (color, strokes)) in layers.into_iter().enumerate() {
+        writeln!(
+            out,
+            r#"<g inkscape:groupmode="layer" id="layer{n}" inkscape:label="{n} {color}" fill="none" stroke="{color}" stroke-width="{pen}" stroke-linecap="round" stroke-linejoin="round">"#,
+            n = n + 1,
+            color = hex(color),
+            pen = options.pen
+        )?;
+        for (points, closed) in order(strokes) {
+            let mut d = String::new();
+            for (i, (x, y)) in points.iter().enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                d.push_str(&format!("{}{} {}", command, number(*x), number(*y)));
+            }
+            if closed {
+                d.push('Z');
+            }
+            writeln!(out, r#"<path d="{}"/>"#, d)?;
+        }
+        writeln!(out, "</g>")?;
+    }
+    writeln!(out, "</svg>")
+}
+
+/// Write `mesh` as a layered SVG to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    validate(options).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| write(mesh, options, out))
+}
+
+fn validate(options: &Options) -> io::Result<()> {
+    let positive = |v: f32| v > 0.0;
+    if !positive(options.width) || !positive(options.pen) || !options.hatch.is_none_or(positive) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the width, pen and hatch spacing must be positive",
+        ));
+    }
+    Ok(())
+}
+
+// Segments of lines `spacing` apart at `angle` degrees inside `outlines`,
+// on a grid shared by every region so neighbouring hatches line up
+fn hatch(outlines: &[Vec<Point>], spacing: f32, angle: f32) -> Vec<(Point, Point)> {
+    let (sin, cos) = angle.to_radians().sin_cos();
+    // in rotated coordinates the lines are horizontal
+    let rotate = |(x, y): Point| (x * cos + y * sin, y * cos - x * sin);
+    let unrotate = |(u, v): Point| (u * cos - v * sin, u * sin + v * cos);
+    let outlines: Vec<Vec<Point>> = outlines
+        .iter()
+        .map(|outline| outline.iter().copied().map(rotate).collect())
+        .collect();
+    let vs = outlines.iter().flatten().map(|p| p.1);
+    let (low, high) = (
+        vs.clone().fold(f32::INFINITY, f32::min),
+        vs.fold(f32::NEG_INFINITY, f32::max),
+    );
+    let mut segments = vec![];
+    if low > high {
+        return segments;
+    }
+    let mut line = (low / spacing).ceil() as i64;
+    while line as f32 * spacing <= high {
+        let v = line as f32 * spacing;
+        line += 1;
+        let mut crossings = vec![];
+        for outline in &outlines {
+            for (i, a) in outline.iter().enumerate() {
+                let b = outline[(i + 1) % outline.len()];
+                // half open, so a line through a point crosses once
+                if (a.1 <= v) != (b.1 <= v) {
+                    crossings.push(a.0 + (v - a.1) / (b.1 - a.1) * (b.0 - a.0));
+                }
+            }
+        }
+        crossings.sort_by(f32::total_cmp);
+        for pair in crossings.chunks_exact(2) {
+            if pair[1] > pair[0] {
+                segments.push((unrotate((pair[0], v)), unrotate((pair[1], v))));
+            }
+        }
+    }
+    segments
+}
+
+// Strokes as points to draw, and if they close, picking the next one that
+// starts closest to where the pen is, from the top left corner
+fn order(mut strokes: Vec<Stroke>) -> Vec<(Vec<Point>, bool)> {
+    let distance = |a: Point, b: Point| (a.0 - b.0).powi(2) + (a.1 - b.1).powi(2);
+    let mut pen = (0.0, 0.0);
+    let mut ordered = vec![];
+    while !strokes.is_empty() {
+        // index of the stroke and of the point it starts at
+        let mut best = (0, 0, f32::INFINITY);
+        for (i, stroke) in strokes.iter().enumerate() {
+            let starts = match stroke {
+                Stroke::Loop(points) => points.clone(),
+                Stroke::Line(a, b) => vec![*a, *b],
+            };
+            for (j, start) in starts.into_iter().enumerate() {
+                let d = distance(pen, start);
+                if d < best.2 {
+                    best = (i, j, d);
+                }
+            }
+        }
+        let (points, closed) = match strokes.swap_remove(best.0) {
+            Stroke::Loop(mut points) => {
+                points.rotate_left(best.1);
+                pen = points[0];
+                (points, true)
+            }
+            Stroke::Line(a, b) => {
+                let (start, end) = if best.1 == 0 { (a, b) } else { (b, a) };
+                pen = end;
+                (vec![start, end], false)
+            }
+        };
+        ordered.push((points, closed));
+    }
+    ordered
+}
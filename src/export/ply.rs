@@ -0,0 +1,48 @@
+//! ASCII PLY with a color per face.
+
+use super::{number, save_with, upward, vertices};
+use crate::error::Result;
+use crate::mesh::Mesh;
+
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Lift every point by the average luminance of its faces times
+    /// `height`, in pixels. The mesh stays in the z=0 plane when `None`.
+    pub height: Option<f32>,
+}
+
+/// Write `mesh` as PLY in pixel units, x to the right, y up and z towards
+/// the viewer. Faces are wound counter-clockwise seen from above.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    let vertices = vertices(mesh, options.height);
+    let triangles = upward(mesh);
+    writeln!(out, "ply")?;
+    writeln!(out, "format ascii 1.0")?;
+    writeln!(out, "comment traingle mesh, {}x{}", mesh.width, mesh.height)?;
+    writeln!(out, "element vertex {}", vertices.len())?;
+    writeln!(out, "property float x")?;
+    writeln!(out, "property float y")?;
+    writeln!(out, "property float z")?;
+    writeln!(out, "element face {}", triangles.len())?;
+    writeln!(out, "property list uchar int vertex_indices")?;
+    writeln!(out, "property uchar red")?;
+    writeln!(out, "property uchar green")?;
+    writeln!(out, "property uchar blue")?;
+    writeln!(out, "end_header")?;
+    for [x, y, z] in &vertices {
+        writeln!(out, "{} {} {}", number(*x), number(*y), number(*z))?;
+    }
+    for ([a, b, c], [red, green, blue]) in triangles.iter().zip(&mesh.colors) {
+        writeln!(out, "3 {} {} {} {} {} {}", a, b, c, red, green, blue)?;
+    }
+    Ok(())
+}
+
+/// Write `mesh` as PLY to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    save_with(path, |out| write(mesh, options, out))
+}
@@ -0,0 +1,153 @@
+//! Binary STL relief for 3D printing.
+//!
+//! Every point is raised by a height between 0 and 1, the luminance of the
+//! faces around it or a height-map image, and the surface is closed with
+//! walls along its outline and a flat base into a watertight solid.
+
+use super::{normal, save_with, upward, vertices};
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+
+use image::GrayImage;
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Where the heights of the points come from.
+#[derive(Debug, Clone)]
+pub enum Heights {
+    /// Average luminance of the colors of the faces around each point.
+    Luminance,
+    /// Brightness of the pixel of this image under each point, the image is
+    /// stretched over the mesh.
+    Map(GrayImage),
+}
+
+/// Options of [`write`].
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub heights: Heights,
+    /// Width of the relief in mm.
+    pub width: f32,
+    /// Thickness in mm under the lowest point.
+    pub base: f32,
+    /// Thickness in mm added at height 1.
+    pub height: f32,
+    /// Raise dark points instead of bright ones, as in a lithophane lit from
+    /// behind.
+    pub invert: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            heights: Heights::Luminance,
+            width: 100.0,
+            base: 1.0,
+            height: 3.0,
+            invert: false,
+        }
+    }
+}
+
+/// Write `mesh` as a binary STL relief in mm, with the base in the z=0
+/// plane and y pointing up so the image isn't mirrored seen from above.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    validate(options)?;
+    let triangles = solid(mesh, options);
+    let mut header = format!("traingle relief {}x{}", mesh.width, mesh.height).into_bytes();
+    header.resize(80, b' ');
+    out.write_all(&header)?;
+    out.write_all(&(triangles.len() as u32).to_le_bytes())?;
+    for triangle in &triangles {
+        for value in normal(triangle).iter().chain(triangle.iter().flatten()) {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        // attribute byte count, unused
+        out.write_all(&[0, 0])?;
+    }
+    Ok(())
+}
+
+/// Write `mesh` to `path`, see [`write`].
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    validate(options).map_err(|e| Error::io(path, e))?;
+    save_with(path, |out| write(mesh, options, out))
+}
+
+fn validate(options: &Options) -> io::Result<()> {
+    if !(options.width > 0.0 && options.base > 0.0 && options.height >= 0.0) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the width and base must be positive and the height not negative",
+        ));
+    }
+    Ok(())
+}
+
+// Triangles of the closed relief, wound counter-clockwise seen from outside
+fn solid(mesh: &Mesh, options: &Options) -> Vec<[[f32; 3]; 3]> {
+    let scale = options.width / mesh.width as f32;
+    let heights = heights(mesh, &options.heights);
+    let top: Vec<[f32; 3]> = mesh
+        .points
+        .iter()
+        .zip(&heights)
+        .map(|((x, y), h)| {
+            let h = if options.invert { 1.0 - h } else { *h };
+            [
+                x * scale,
+                (mesh.height as f32 - y) * scale,
+                options.base + h * options.height,
+            ]
+        })
+        .collect();
+    let bottom = |i: usize| [top[i][0], top[i][1], 0.0];
+
+    let triangles = upward(mesh);
+    let mut solid = vec![];
+    for &[a, b, c] in &triangles {
+        solid.push([top[a], top[b], top[c]]);
+        solid.push([bottom(a), bottom(c), bottom(b)]);
+    }
+    // a side without its reverse in another face is on the outline, with the
+    // surface on its left
+    let sides: HashSet<(usize, usize)> = triangles
+        .iter()
+        .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+        .collect();
+    for &[a, b, c] in &triangles {
+        for (u, v) in [(a, b), (b, c), (c, a)] {
+            if !sides.contains(&(v, u)) {
+                solid.push([top[u], bottom(u), bottom(v)]);
+                solid.push([top[u], bottom(v), top[v]]);
+            }
+        }
+    }
+    solid
+}
+
+// Height of every point, 0 to 1
+fn heights(mesh: &Mesh, heights: &Heights) -> Vec<f32> {
+    match heights {
+        // z of the points lifted by their luminance times 1
+        Heights::Luminance => vertices(mesh, Some(1.0)).iter().map(|v| v[2]).collect(),
+        Heights::Map(map) => {
+            let (width, height) = map.dimensions();
+            let pixel = |v: f32, size: u32, of: u32| {
+                ((v / of as f32 * size as f32).floor().max(0.0) as u32).min(size - 1)
+            };
+            mesh.points
+                .iter()
+                .map(|(x, y)| {
+                    if width == 0 || height == 0 {
+                        return 0.0;
+                    }
+                    let luma =
+                        map.get_pixel(pixel(*x, width, mesh.width), pixel(*y, height, mesh.height));
+                    luma.0[0] as f32 / 255.0
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! SVG with one polygon per face.
+
+use super::{hex, number, save_with};
+use crate::error::Result;
+use crate::mesh::Mesh;
+use crate::region::{self, Region};
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Options of [`write`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    /// Put the shapes of each color in a `<g>` carrying the fill.
+    pub group_by_color: bool,
+    /// Merge adjacent faces of the same color into a single `<path>`.
+    pub merge: bool,
+}
+
+/// Write `mesh` as SVG, the viewBox matches the image it was evolved on.
+pub fn write<W: Write>(mesh: &Mesh, options: &Options, out: &mut W) -> io::Result<()> {
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = mesh.width,
+        h = mesh.height
+    )?;
+    // shape of each face or region with its color, in mesh order
+    let shapes: Vec<([u8; 3], String)> = if options.merge {
+        region::regions(mesh)
+            .iter()
+            .map(|region| (region.color, path(mesh, region)))
+            .collect()
+    } else {
+        mesh.triangles
+            .iter()
+            .zip(&mesh.colors)
+            .map(|(triangle, color)| (*color, polygon(mesh, triangle)))
+            .collect()
+    };
+    if options.group_by_color {
+        let mut groups: BTreeMap<[u8; 3], Vec<&str>> = BTreeMap::new();
+        for (color, shape) in &shapes {
+            groups.entry(*color).or_default().push(shape);
+        }
+        for (color, shapes) in groups {
+            writeln!(out, r#"<g fill="{}">"#, hex(color))?;
+            for shape in shapes {
+                writeln!(out, "{}/>", shape)?;
+            }
+            writeln!(out, "</g>")?;
+        }
+    } else {
+        for (color, shape) in &shapes {
+            writeln!(out, r#"{} fill="{}"/>"#, shape, hex(*color))?;
+        }
+    }
+    writeln!(out, "</svg>")
+}
+
+/// Write `mesh` as SVG to `path`.
+pub fn save(mesh: &Mesh, options: &Options, path: &Path) -> Result<()> {
+    save_with(path, |out| write(mesh, options, out))
+}
+
+// Unclosed element, the caller adds the fill if needed
+fn polygon(mesh: &Mesh, triangle: &[usize; 3]) -> String {
+    let points: Vec<String> = triangle
+        .iter()
+        .map(|i| {
+            let (x, y) = mesh.points[*i];
+            format!("{},{}", number(x), number(y))
+        })
+        .collect();
+    format!(r#"<polygon points="{}""#, points.join(" "))
+}
+
+fn path(mesh: &Mesh, region: &Region) -> String {
+    let mut d = String::new();
+    for outline in &region.outlines {
+        for (n, i) in outline.iter().enumerate() {
+            let (x, y) = mesh.points[*i];
+            let command = if n == 0 { 'M' } else { 'L' };
+            d.push_str(&format!("{}{} {}", command, number(x), number(y)));
+        }
+        d.push('Z');
+    }
+    format!(r#"<path d="{}""#, d)
+}
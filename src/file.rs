@@ -0,0 +1,11 @@
+use super::error::{Error, Result};
+
+use std::path::Path;
+
+// Write `contents` next to `path` first and move it in place, so a run killed
+// while writing leaves the previous file intact
+pub(crate) fn replace(path: &Path, contents: &[u8]) -> Result<()> {
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, contents).map_err(|e| Error::io(&partial, e))?;
+    std::fs::rename(&partial, path).map_err(|e| Error::io(path, e))
+}
@@ -1,29 +1,40 @@
 use super::cache::Cache;
-use super::face::{Face, FaceFinder};
-use super::geom::{Point, Triangle};
+use super::config::Config;
+use super::error::{Error, Result};
+use super::face::Face;
+use super::geom::Point;
 use super::img::Img;
 use super::member::{Member, MemberType};
 
+use rand::Rng;
 use spade::delaunay::FloatDelaunayTriangulation;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::rc::Rc;
 //use spade::delaunay::{DelaunayTriangulation, DelaunayWalkLocate, FloatDelaunayTriangulation};
 
-const FACE_SIZE_THRESHOLD: f32 = 10.0;
-
 pub struct Generation<'a> {
     base: Vec<Rc<RefCell<Member>>>,
     pub img: &'a Img,
-    mutations: usize,
     populations: Vec<Population>,
     pub cache: &'a mut Cache,
+    pub config: &'a Config,
+    average_fitness: f32,
 }
 
 pub struct Population {
     pub faces: Vec<Face>,
     // del: DelaunayTriangulation<Point, FloatKernel, DelaunayWalkLocate>,
     pub points: Vec<(f32, f32)>,
+    // index of every point in the population it was evolved from, empty when unknown
+    pub origins: Vec<usize>,
+}
+
+// Points of the best faces, with the id of the member each one comes from
+struct BestPoints {
+    points: Vec<(f32, f32)>,
+    origins: Vec<usize>,
+    average_fitness: f32,
 }
 
 impl Population {
@@ -32,15 +43,29 @@ impl Population {
         //del: DelaunayTriangulation<Point, FloatKernel, DelaunayWalkLocate>,
         points: Vec<(f32, f32)>,
     ) -> Population {
-        Population { faces, points }
+        Population {
+            faces,
+            points,
+            origins: vec![],
+        }
+    }
+    // Sum of the fitness of every face
+    pub fn fitness(&self) -> f32 {
+        self.faces.iter().map(|face| face.fitness).sum()
     }
 }
 
 impl<'a> Generation<'a> {
-    pub fn new(previous: Population, img: &'a Img, cache: &'a mut Cache) -> Generation<'a> {
+    pub fn new(
+        previous: &Population,
+        img: &'a Img,
+        cache: &'a mut Cache,
+        config: &'a Config,
+    ) -> Result<Generation<'a>> {
         let base: Vec<Rc<RefCell<Member>>> = previous
             .points
-            .into_iter()
+            .iter()
+            .copied()
             .enumerate()
             .map(|(id, p)| {
                 Rc::new(RefCell::new(Member::new(
@@ -49,6 +74,7 @@ impl<'a> Generation<'a> {
                     p,
                     img.dimensions(),
                     img.points(),
+                    config.member,
                 )))
             })
             .collect();
@@ -56,17 +82,23 @@ impl<'a> Generation<'a> {
         let mut gen = Generation {
             base: vec![],
             img,
-            mutations: 0,
             populations: vec![],
             cache,
+            config,
+            average_fitness: 0.0,
         };
 
-        let pop = Generation::triangulate(&mut gen, &base);
+        let pop = Generation::triangulate(&mut gen, &base)?;
         gen.populations.push(pop);
         gen.base = base;
-        gen
+        Ok(gen)
     }
-    pub fn from(points: Vec<(f32, f32)>, img: &'a Img, cache: &'a mut Cache) -> Generation<'a> {
+    pub fn from(
+        points: Vec<(f32, f32)>,
+        img: &'a Img,
+        cache: &'a mut Cache,
+        config: &'a Config,
+    ) -> Result<Generation<'a>> {
         let base: Vec<Rc<RefCell<Member>>> = points
             .into_iter()
             .enumerate()
@@ -77,6 +109,7 @@ impl<'a> Generation<'a> {
                     p,
                     img.dimensions(),
                     img.points(),
+                    config.member,
                 )))
             })
             .collect();
@@ -84,23 +117,37 @@ impl<'a> Generation<'a> {
         let mut gen = Generation {
             base: vec![],
             img,
-            mutations: 0,
             populations: vec![],
             cache,
+            config,
+            average_fitness: 0.0,
         };
 
-        let pop = Generation::triangulate(&mut gen, &base);
+        let pop = Generation::triangulate(&mut gen, &base)?;
         gen.populations.push(pop);
         gen.base = base;
-        gen
+        Ok(gen)
+    }
+    // Population the generation was created from, scored against the image
+    pub fn current(&self) -> &Population {
+        &self.populations[0]
     }
-    pub fn mutate(&mut self, n: u32) -> () {
+    // Returns false when `cancelled` stopped the rounds, the generation is then incomplete
+    pub fn mutate<R: Rng>(
+        &mut self,
+        n: u32,
+        rng: &mut R,
+        cancelled: &mut dyn FnMut() -> bool,
+    ) -> Result<bool> {
         for _i in 0..n {
+            if cancelled() {
+                return Ok(false);
+            }
             let mut members = vec![];
             for point in &mut self.base {
-                members.push(point.borrow_mut().mutate());
+                members.push(point.borrow_mut().mutate(rng));
             }
-            let pop = Generation::triangulate(self, &members);
+            let pop = Generation::triangulate(self, &members)?;
             self.populations.push(pop);
         }
 
@@ -109,12 +156,16 @@ impl<'a> Generation<'a> {
         for base_member in &self.base {
             members.push(base_member.borrow_mut().merge_mutations_into_base());
         }
-        let pop = Generation::triangulate(self, &members);
+        let pop = Generation::triangulate(self, &members)?;
         self.populations.push(pop);
+        Ok(true)
     }
     // Create triangles from a set of points
     // Calculate fitness of each triangle and aggregate in each member
-    fn triangulate(generation: &mut Generation, members: &Vec<Rc<RefCell<Member>>>) -> Population {
+    fn triangulate(
+        generation: &mut Generation,
+        members: &[Rc<RefCell<Member>>],
+    ) -> Result<Population> {
         // Calculate delaunay triangles from points
         let mut delaunay = FloatDelaunayTriangulation::with_walk_locate();
         let mut points = vec![];
@@ -127,13 +178,28 @@ impl<'a> Generation<'a> {
         let mut faces: Vec<Face> = vec![];
         for face in delaunay.triangles() {
             let triangle = face.as_triangle();
-            faces.push(Face::new(triangle, members, generation));
+            faces.push(Face::new(triangle, members, generation)?);
+        }
+        if faces.is_empty() {
+            return Err(Error::Geometry(format!(
+                "{} points do not form a single triangle",
+                points.len()
+            )));
         }
 
-        Population::new(faces, points)
+        Ok(Population::new(faces, points))
+    }
+    // Average fitness of the members picked by get_best_population
+    pub fn average_fitness(&self) -> f32 {
+        self.average_fitness
     }
-    pub fn get_best_population(&mut self) -> Population {
-        let points = self.get_best_points();
+    pub fn get_best_population(&mut self) -> Result<Population> {
+        let BestPoints {
+            points,
+            origins,
+            average_fitness,
+        } = self.get_best_points()?;
+        self.average_fitness = average_fitness;
 
         let mut delaunay = FloatDelaunayTriangulation::with_walk_locate();
         let mut members = vec![];
@@ -145,106 +211,81 @@ impl<'a> Generation<'a> {
                 point,
                 self.img.dimensions(),
                 self.img.points(),
+                self.config.member,
             ))));
         }
 
         let mut faces: Vec<Face> = vec![];
         for face in delaunay.triangles() {
             let triangle = face.as_triangle();
-            faces.push(Face::new(triangle, &members, self));
+            faces.push(Face::new(triangle, &members, self)?);
         }
 
-        Population::new(faces, points)
+        let mut population = Population::new(faces, points);
+        population.origins = origins;
+        Ok(population)
     }
-    fn get_best_points(&self) -> Vec<(f32, f32)> {
+    fn get_best_points(&self) -> Result<BestPoints> {
         let mut sorted_faces = vec![];
         for pop in &self.populations {
             for face in &pop.faces {
+                if face.fitness.is_nan() {
+                    let (v0, v1, v2) = face.triangle.vertices;
+                    return Err(Error::Geometry(format!(
+                        "triangle ({}, {}), ({}, {}), ({}, {}) has no fitness",
+                        v0.0, v0.1, v1.0, v1.1, v2.0, v2.1
+                    )));
+                }
                 sorted_faces.push(face);
             }
         }
-        sorted_faces.sort_by(|a, b| b.fitness.partial_cmp(&a.fitness).unwrap());
+        // no NaN left, so every pair is comparable
+        sorted_faces.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
 
         let mut points: Vec<(f32, f32)> = vec![];
+        let mut origins = vec![];
         let mut seen: HashSet<usize> = HashSet::new();
         let mut sum = 0.0;
-        for i in 0..sorted_faces.len() {
+        for face in sorted_faces {
             if points.len() >= self.img.points() as usize {
                 break;
             }
-            let face = sorted_faces[i];
+            if face.triangle.area() < self.config.generation.face_size_threshold {
+                continue;
+            }
             let m1 = face.points.0.borrow();
             if !seen.contains(&m1.id) {
                 points.push(m1.point.values());
+                origins.push(m1.id);
                 seen.insert(m1.id);
                 sum += m1.fitness;
             }
             let m2 = face.points.1.borrow();
             if !seen.contains(&m2.id) {
                 points.push(m2.point.values());
+                origins.push(m2.id);
                 seen.insert(m2.id);
                 sum += m2.fitness;
             }
             let m3 = face.points.2.borrow();
             if !seen.contains(&m3.id) {
                 points.push(m3.point.values());
+                origins.push(m3.id);
                 seen.insert(m3.id);
                 sum += m3.fitness
             }
         }
-        println!("average fitness {}", sum / points.len() as f32);
-        points
-    }
-    pub fn write(&self, filename: String, population: &mut Population) -> () {
-        let (width, height) = self.img.dimensions();
-
-        // Rasterize image
-        let num_pixels = (width * height) as u32;
-        let mut face_finder = FaceFinder::new(&mut population.faces);
-        let mut buf = vec![];
-        '_outer_raster: for i in 0..num_pixels {
-            // find containing triangle
-            let x = i as f32 % width;
-            let y = i as f32 / width;
-            let face = face_finder.find(x, y);
-            let [r, g, b] = match face {
-                Some(f) => f.color.0,
-                None => {
-                    // try to use a nearby pixel
-                    let found = if x == 0.0 && y == 0.0 {
-                        None
-                    } else if x == 0.0 {
-                        face_finder.find(x, y - 1.0)
-                    } else if y == 0.0 {
-                        face_finder.find(x - 1.0, y)
-                    } else {
-                        face_finder.find(x - 1.0, y - 1.0)
-                    };
-                    match found {
-                        Some(f) => f.color.0,
-                        None => [0, 255, 255],
-                    }
-                }
-            };
-            buf.push(r as u8);
-            buf.push(g as u8);
-            buf.push(b as u8);
-        }
-
-        match image::save_buffer(
-            filename,
-            &buf[..],
-            width as u32,
-            height as u32,
-            image::ColorType::Rgb8,
-        ) {
-            Ok(_) => println!(
-                "done, face: {}, pixels: {}/{}",
-                population.faces.len(),
-                buf.len(),
-                width * height * 3.0
-            ),
-            Err(e) => println!("error {}", e),
+        if points.len() < 3 {
+            return Err(Error::Geometry(format!(
+                "no triangle covers {} pixels, lower generation.face_size_threshold",
+                self.config.generation.face_size_threshold
+            )));
         }
+        let average = sum / points.len() as f32;
+        Ok(BestPoints {
+            points,
+            origins,
+            average_fitness: average,
+        })
     }
 }
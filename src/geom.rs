@@ -2,4 +2,4 @@ mod point;
 mod triangle;
 
 pub use point::Point;
-pub use triangle::Triangle;
+pub use triangle::{contains, Triangle};
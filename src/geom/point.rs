@@ -19,18 +19,18 @@ impl Point {
     pub fn mutate(&mut self, delta: Point, (width, height): (f32, f32)) -> Point {
         let mut x = self.0;
         let mut y = self.1;
-        if x != 0.0 && x != width as f32 {
+        if x != 0.0 && x != width {
             x += delta.0;
-            if x > width as f32 {
-                x = width as f32;
+            if x > width {
+                x = width;
             } else if x < 0.0 {
                 x = 0.0
             }
         }
-        if y != 0.0 && y != height as f32 {
+        if y != 0.0 && y != height {
             y += delta.1;
-            if y > height as f32 {
-                y = height as f32;
+            if y > height {
+                y = height;
             } else if self.1 < 0.0 {
                 y = 0.0;
             }
@@ -75,7 +75,7 @@ impl std::cmp::PartialEq for Point {
 impl Copy for Point {}
 impl Clone for Point {
     fn clone(&self) -> Self {
-        Self::new(self.0, self.1)
+        *self
     }
 }
 
@@ -14,7 +14,7 @@ impl Img {
     pub fn get_pixel(&self, x: u32, y: u32) -> image::Rgb<u8> {
         *self.0.get_pixel(x, y)
     }
-    pub fn image(&self) -> image::ImageBuffer<image::Rgb<u8>, Vec<u8>> {
-        self.0.clone()
+    pub fn image(&self) -> &image::ImageBuffer<image::Rgb<u8>, Vec<u8>> {
+        &self.0
     }
 }
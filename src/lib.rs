@@ -0,0 +1,52 @@
+//! Evolves low-poly triangulations of images.
+//!
+//! ```no_run
+//! use traingle::Triangulator;
+//!
+//! let image = image::open("photo.jpg").unwrap().to_rgb8();
+//! let mesh = Triangulator::new()
+//!     .segments(20)
+//!     .generations(10)
+//!     .run(&image)
+//!     .unwrap();
+//! mesh.render((mesh.width * 4, mesh.height * 4))
+//!     .unwrap()
+//!     .save("poster.png")
+//!     .unwrap();
+//! ```
+
+pub mod animation;
+mod cache;
+mod checkpoint;
+pub mod codec;
+pub mod config;
+mod error;
+pub mod export;
+mod face;
+mod file;
+mod generation;
+mod geom;
+mod img;
+mod member;
+mod mesh;
+pub mod metadata;
+mod morph;
+mod observer;
+mod palette;
+mod pixel_group;
+mod png;
+mod region;
+mod render;
+mod score;
+mod triangulator;
+pub mod y4m;
+
+pub use checkpoint::Checkpoint;
+pub use config::Config;
+pub use error::{Error, Result};
+pub use image;
+pub use mesh::{Mesh, RunInfo};
+pub use morph::Morph;
+pub use observer::{Observer, Stats};
+pub use score::Score;
+pub use triangulator::{Evolution, Stop, Triangulator};
@@ -1,90 +1,638 @@
-mod img;
-mod face;
-mod geom;
-mod cache;
-mod member;
-mod generation;
-mod pixel_group;
-
-use img::Img;
-use generation::Generation;
-use cache::Cache;
+mod cli;
 
-use std::time::Instant;
+use cli::{
+    Cli, Command, DecodeArgs, EncodeArgs, EvolveArgs, ExportArgs, InspectArgs, MorphArgs,
+    NumbersArgs, RenderArgs, ScoreArgs,
+};
+use traingle::animation::{self, Animation};
+use traingle::codec;
+use traingle::export::{dxf, gltf, html, numbers, obj, pdf, plotter, ply, stl, svg};
+use traingle::metadata::Metadata;
+use traingle::y4m::Y4m;
+use traingle::{
+    Checkpoint, Error, Mesh, Morph, Observer, Result, Score, Stats, Stop, Triangulator,
+};
+
+use clap::Parser;
 use image::io::Reader as ImageReader;
+use image::RgbImage;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+// Saved next to the rendered generations, updated after every generation
+const MESH_FILENAME: &str = "mesh.json";
+// Generation with the best PSNR, written when the run ends
+const BEST_FILENAME: &str = "best";
+// Written every output.checkpoint_interval generations and when the run ends
+const CHECKPOINT_FILENAME: &str = "checkpoint.json";
+
+fn open_image(path: &Path) -> Result<RgbImage> {
+    let reader = ImageReader::open(path).map_err(|e| Error::io(path, e))?;
+    let image = reader.decode().map_err(|e| Error::Decode {
+        path: path.to_path_buf(),
+        source: e,
+    })?;
+    Ok(image.to_rgb8())
+}
+
+fn save_image(image: &RgbImage, path: &Path, format: image::ImageFormat) -> Result<()> {
+    image
+        .save_with_format(path, format)
+        .map_err(|e| Error::Encode {
+            path: path.to_path_buf(),
+            source: e,
+        })
+}
+
+// Save a render of `mesh` with the metadata of the run it comes from
+fn save_render(
+    image: &RgbImage,
+    path: &Path,
+    format: image::ImageFormat,
+    input: &Path,
+    image_sha256: &str,
+    mesh: &Mesh,
+) -> Result<()> {
+    match Metadata::new(input, image_sha256, mesh) {
+        Some(metadata) => metadata.save_image(image, path, format),
+        None => save_image(image, path, format),
+    }
+}
 
-const SEGMENTS: u32 = 35;
-const GENERATIONS: u32 = 20;
-const MUTATIONS_PER_GENERATION: u32 = 10;
+// Where evolve prints its progress, stderr when stdout carries a video stream
+#[derive(Clone, Copy)]
+struct Log {
+    stderr: bool,
+}
 
-fn get_points((w, h): (f32, f32)) -> Vec<(f32, f32)> {
-    // Create random points across image
-    let mut points = vec![];
-    for i in 0..SEGMENTS {
-        for j in 0..SEGMENTS {
-            points.push((
-                i as f32 * (w / (SEGMENTS - 1) as f32),
-                j as f32 * (h / (SEGMENTS - 1) as f32),
-            ));
+impl Log {
+    fn line(&self, line: std::fmt::Arguments) {
+        if self.stderr {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
         }
     }
-    points
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = std::env::args();
-    args.next();
-    let filename = args.next().unwrap();
-    let img = Img::new(
-        ImageReader::open(filename)?.decode()?.to_rgb8(),
-        SEGMENTS.pow(2) as f32,
-    );
-    println!("(w, h): {:?}", img.dimensions());
+// Prints the progress of evolve
+struct Reporter {
+    log: Log,
+}
+
+impl Observer for Reporter {
+    fn generation_finished(&mut self, stats: &Stats, _mesh: &Mesh) {
+        self.log.line(format_args!(
+            "Generation {}, generated in {:.2}s: {} faces, psnr {:.3} dB{}, average fitness {:.3}, face fitness {:.3} to {:.3}.",
+            stats.generation,
+            stats.elapsed.as_secs_f64(),
+            stats.faces,
+            stats.psnr,
+            if stats.accepted { "" } else { " (rejected)" },
+            stats.average_fitness,
+            stats.min_face_fitness,
+            stats.max_face_fitness,
+        ));
+    }
+    fn stopped(&mut self, stop: Stop) {
+        self.log.line(format_args!("stopped: {}", stop));
+    }
+}
+
+fn main() -> ExitCode {
+    let (input, result) = match Cli::parse().command {
+        Command::Evolve(args) => (args.input.clone(), evolve(args)),
+        Command::Render(args) => (args.mesh.clone(), render(args)),
+        Command::Score(args) => (args.mesh.clone(), score(args)),
+        Command::Inspect(args) => (args.file.clone(), inspect(args)),
+        Command::Export(args) => (args.meshes[0].clone(), export(args)),
+        Command::Encode(args) => (args.mesh.clone(), encode(args)),
+        Command::Decode(args) => (args.input.clone(), decode(args)),
+        Command::Morph(args) => (args.from.clone(), morph(args)),
+        Command::Numbers(args) => (args.mesh.clone(), numbers(args)),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            match e {
+                // name the input the engine failed on
+                Error::Geometry(_) => eprintln!("error: {}: {}", input.display(), e),
+                _ => eprintln!("error: {}", e),
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
 
-    let mut cache = Cache::new();
+fn evolve(args: EvolveArgs) -> Result<()> {
+    let checkpoint = match &args.resume {
+        Some(path) => Some(Checkpoint::load(path)?),
+        None => None,
+    };
+    let from = match &args.from {
+        Some(path) => Some(Mesh::load(path)?),
+        None => None,
+    };
+    let mut config = match (&checkpoint, &from) {
+        (Some(checkpoint), _) => args.apply(checkpoint.config.clone())?,
+        // keep the options of the run the mesh comes from, but not its seed
+        (None, Some(Mesh { run: Some(run), .. })) if args.config.is_none() => {
+            let mut config = run.config.clone();
+            config.run.seed = None;
+            args.apply(config)?
+        }
+        _ => args.config()?,
+    };
+    let image = open_image(&args.input)?;
+    // renders record the input they were evolved from
+    let image_sha256 = Metadata::hash_file(&args.input)?;
+    std::fs::create_dir_all(&config.output.dir).map_err(|e| Error::io(&config.output.dir, e))?;
+    let log = Log { stderr: args.y4m };
+    log.line(format_args!("(w, h): {:?}", image.dimensions()));
 
-    let now = Instant::now();
-    let mut previous;
+    // Ctrl-C stops the run after the current generation
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let flag = interrupt.clone();
+    if let Err(e) = ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst)) {
+        eprintln!("warning: Ctrl-C will not write the best mesh: {}", e);
+    }
 
-    // Calculate fitness and create 0th generation
-    let initial_points = get_points(img.dimensions());
+    let triangulator = Triangulator::new()
+        .config(config.clone())
+        .interrupt(interrupt);
+    let mut evolution = match checkpoint {
+        Some(mut checkpoint) => {
+            checkpoint.config = config.clone();
+            triangulator.resume(&image, checkpoint)?
+        }
+        None => match &from {
+            Some(mesh) => triangulator.evolve_from(&image, mesh)?,
+            None => triangulator.evolve(&image)?,
+        },
+    };
+    evolution.observe(Reporter { log });
+    log.line(format_args!("seed: {}", evolution.seed()));
+    // Record the effective config so the run can be reproduced
+    config.run.seed = Some(evolution.seed());
+    config.save(&config.output.dir.join("config.toml"))?;
+    let checkpoint_path = config.output.dir.join(CHECKPOINT_FILENAME);
+    let interval = config.output.checkpoint_interval;
+    // frames are written as they come, only the file handle is kept
+    let animation_path = config
+        .animation
+        .file
+        .as_ref()
+        .map(|f| config.output.dir.join(f));
+    let mut animation_file = match &animation_path {
+        Some(path) => Some(BufWriter::new(
+            File::create(path).map_err(|e| Error::io(path, e))?,
+        )),
+        None => None,
+    };
+    let frame_size = config.animation.size(image.dimensions());
+    let mut animation = match (&mut animation_file, &animation_path) {
+        (Some(file), Some(path)) => {
+            let format = animation::Format::from_path(path).expect("validated with the config");
+            let timing = config.animation.timing();
+            Some(Animation::new(file, format, frame_size, timing).map_err(|e| Error::io(path, e))?)
+        }
+        _ => None,
+    };
+    let stdout = std::io::stdout();
+    let stdout_path = Path::new("<stdout>");
+    let mut video = if args.y4m {
+        let out = BufWriter::new(stdout.lock());
+        Some(Y4m::new(out, image.dimensions(), args.fps).map_err(|e| Error::io(stdout_path, e))?)
+    } else {
+        None
+    };
+    let tween = if animation.is_some() || video.is_some() {
+        config.animation.tween
+    } else {
+        0
+    };
+    // last generation in the animation and video, the tweens start from it
+    let mut last: Option<Mesh> = None;
+    let mut result = Ok(());
+    loop {
+        let generation = evolution.generation();
+        let mesh = match evolution.next() {
+            Some(Ok(mesh)) => mesh,
+            Some(Err(e)) => {
+                result = Err(e);
+                break;
+            }
+            None => break,
+        };
+        let now = Instant::now();
 
-    let mut gen = Generation::from(initial_points, &img, &mut cache);
-    previous = gen.get_best_population();
-    let time_to_generate = now.elapsed().as_secs();
+        // a failed write ends the run, but the best generation is still kept
+        let path = config.output.path(generation);
+        let written = (|| -> Result<()> {
+            let rendered = mesh.render(image.dimensions())?;
+            let format = config.output.format.image_format();
+            save_render(&rendered, &path, format, &args.input, &image_sha256, &mesh)?;
+            mesh.save(&config.output.dir.join(MESH_FILENAME))?;
+            if config.output.meshes {
+                mesh.save(&config.output.mesh_path(generation))?;
+            }
+            let tweens: Vec<Mesh> = match &last {
+                Some(last) => Morph::new(last, &mesh).frames(tween).collect(),
+                None => vec![],
+            };
+            for tween in &tweens {
+                if let (Some(animation), Some(path)) = (&mut animation, &animation_path) {
+                    let frame = tween.render(frame_size)?;
+                    animation.push(frame).map_err(|e| Error::io(path, e))?;
+                }
+                if let Some(video) = &mut video {
+                    let frame = tween.render(image.dimensions())?;
+                    video.push(&frame).map_err(|e| Error::io(stdout_path, e))?;
+                }
+            }
+            if let Some(video) = &mut video {
+                video
+                    .push(&rendered)
+                    .map_err(|e| Error::io(stdout_path, e))?;
+            }
+            if let (Some(animation), Some(path)) = (&mut animation, &animation_path) {
+                let frame = if frame_size == rendered.dimensions() {
+                    rendered
+                } else {
+                    mesh.render(frame_size)?
+                };
+                animation.push(frame).map_err(|e| Error::io(path, e))?;
+            }
+            if interval > 0 && (generation + 1) % interval == 0 {
+                if let Some(checkpoint) = evolution.checkpoint() {
+                    checkpoint.save(&checkpoint_path)?;
+                }
+            }
+            Ok(())
+        })();
+        if let Err(e) = written {
+            result = Err(e);
+            break;
+        }
+        log.line(format_args!(
+            "written {} in {:.2}s.",
+            path.display(),
+            now.elapsed().as_secs_f64()
+        ));
+        if tween > 0 {
+            last = Some(mesh);
+        }
+    }
+
+    // the first error is the one reported
+    if interval > 0 {
+        if let Some(checkpoint) = evolution.checkpoint() {
+            result = result.and(checkpoint.save(&checkpoint_path));
+        }
+    }
+    let mut animated = false;
+    if let (Some(animation), Some(path)) = (animation, &animation_path) {
+        if animation.frames() > 0 {
+            animated = true;
+            let finished = animation.finish().map_err(|e| Error::io(path, e));
+            if finished.is_ok() {
+                log.line(format_args!("written {}", path.display()));
+            }
+            result = result.and(finished);
+        }
+    }
+    if let (Some(mut file), Some(path)) = (animation_file, &animation_path) {
+        if animated {
+            result = result.and(file.flush().map_err(|e| Error::io(path, e)));
+        } else {
+            // no generation was written, don't leave a file with only a header
+            drop(file);
+            result = result.and(std::fs::remove_file(path).map_err(|e| Error::io(path, e)));
+        }
+    }
+    // Keep the best generation even when the run failed or was cut short
+    if let Some(best) = evolution.best() {
+        let dir = &config.output.dir;
+        let format = config.output.format;
+        result = result.and(best.save(&dir.join(BEST_FILENAME).with_extension("json")));
+        result = result.and(best.render(image.dimensions()).and_then(|rendered| {
+            save_render(
+                &rendered,
+                &dir.join(BEST_FILENAME).with_extension(format.extension()),
+                format.image_format(),
+                &args.input,
+                &image_sha256,
+                best,
+            )
+        }));
+    }
+    result
+}
 
-    gen.write(String::from("output/output-0.jpg"), &mut previous);
+fn render(args: RenderArgs) -> Result<()> {
+    let mesh = Mesh::load(&args.mesh)?;
+    let size = args.size(mesh.dimensions())?;
+    let format = image::ImageFormat::from_path(&args.output).map_err(|e| Error::Encode {
+        path: args.output.clone(),
+        source: e,
+    })?;
+    save_image(&mesh.render(size)?, &args.output, format)?;
     println!(
-        "Generation 0, generated in {}s, written in {}s.",
-        time_to_generate,
-        now.elapsed().as_secs() - time_to_generate,
+        "rendered {}x{} to {}",
+        size.0,
+        size.1,
+        args.output.display()
     );
+    Ok(())
+}
 
-    // Generation loop:
-    for i in 0..GENERATIONS {
-        let now = Instant::now();
+fn score(args: ScoreArgs) -> Result<()> {
+    let triangulator = Triangulator::new().config(args.config()?);
+    let mesh = Mesh::load(&args.mesh)?;
+    let image = open_image(&args.image)?;
+
+    let score = triangulator.score(&mesh, &image)?;
+    println!("mse: {:.3}", score.mse);
+    println!("psnr: {:.3} dB", score.psnr);
+    match score.fitness {
+        Some(fitness) => {
+            println!("fitness: {}", fitness);
+            println!(
+                "average face fitness: {}",
+                fitness / mesh.triangles.len() as f32
+            );
+        }
+        // face fitness is only meaningful in the coordinates the mesh was evolved in
+        None => println!(
+            "fitness: skipped, mesh is {}x{} but image is {}x{}",
+            mesh.width,
+            mesh.height,
+            image.width(),
+            image.height()
+        ),
+    }
+    Ok(())
+}
 
-        // - Create generation from previous generation (new base members)
-        let mut gen = Generation::new(previous, &img, &mut cache);
-        // - Mutate each base member equal number of times
-        // - Calculate fitness of each new member
-        // - If fitness is higher than base member, its marked as beneficial
-        // - Base members are copied again, mutating them with all beneficial mutations
-        // - Calculate fitness of new mutated base members
-        gen.mutate(MUTATIONS_PER_GENERATION);
+fn inspect(args: InspectArgs) -> Result<()> {
+    // an image is a render, anything else a mesh
+    if image::ImageFormat::from_path(&args.file).is_ok() {
+        return inspect_render(&args.file);
+    }
+    let mesh = Mesh::load(&args.file)?;
 
-        // - Sort all members by fitness
-        previous = gen.get_best_population();
-        let time_to_generate = now.elapsed().as_secs();
+    let colors: HashSet<[u8; 3]> = mesh.colors.iter().copied().collect();
+    let areas: Vec<f32> = (0..mesh.triangles.len())
+        .map(|i| mesh.triangle_area(i))
+        .collect();
+    let total_area: f32 = areas.iter().sum();
+    let min_area = areas.iter().copied().fold(f32::INFINITY, f32::min);
+    let max_area = areas.iter().copied().fold(0.0, f32::max);
 
-        gen.write(format!("output/output-{}.jpg", i + 1), &mut previous);
+    println!("dimensions: {}x{}", mesh.width, mesh.height);
+    println!("points: {}", mesh.points.len());
+    println!("triangles: {}", mesh.triangles.len());
+    println!("colors: {}", colors.len());
+    if !areas.is_empty() {
         println!(
-            "Generation {}, generated in {}s, written in {}s.",
-            i + 1,
-            time_to_generate,
-            now.elapsed().as_secs() - time_to_generate,
+            "triangle area: min {:.1}, mean {:.1}, max {:.1}",
+            min_area,
+            total_area / areas.len() as f32,
+            max_area
         );
     }
+    println!(
+        "coverage: {:.1}%",
+        100.0 * total_area / (mesh.width * mesh.height) as f32
+    );
+    Ok(())
+}
+
+fn inspect_render(path: &Path) -> Result<()> {
+    let metadata = Metadata::load(path)?.ok_or_else(|| Error::Metadata {
+        path: Some(path.to_path_buf()),
+        message: "none recorded, only renders written by evolve carry it".to_string(),
+    })?;
+    println!("input: {}", metadata.input);
+    println!("image sha256: {}", metadata.image_sha256);
+    println!("seed: {}", metadata.seed);
+    println!("generation: {}", metadata.generation);
+    println!("fitness: {}", metadata.fitness);
+    let config = toml::to_string_pretty(&metadata.config).map_err(|e| Error::Metadata {
+        path: Some(path.to_path_buf()),
+        message: e.to_string(),
+    })?;
+    println!("config:\n{}", config.trim_end());
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> Result<()> {
+    let meshes = args
+        .meshes
+        .iter()
+        .map(|path| Mesh::load(path))
+        .collect::<Result<Vec<Mesh>>>()?;
+    let extension = args
+        .output
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+    // only the viewer shows several generations
+    if meshes.len() > 1 && extension.as_deref() != Some("html") {
+        return Err(Error::Config {
+            path: None,
+            message: format!(
+                "can not export {} meshes to {}, only .html takes several",
+                meshes.len(),
+                args.output.display()
+            ),
+        });
+    }
+    let mesh = &meshes[0];
+    match extension.as_deref() {
+        Some("html") => html::save(&meshes, &args.html(), &args.output)?,
+        Some("svg") if args.layers => plotter::save(mesh, &args.plotter(), &args.output)?,
+        Some("svg") => svg::save(mesh, &args.svg(), &args.output)?,
+        Some("pdf") => pdf::save(mesh, &args.pdf(), &args.output)?,
+        Some("obj") => obj::save(mesh, &args.obj(), &args.output)?,
+        Some("ply") => ply::save(mesh, &args.ply(), &args.output)?,
+        Some("dxf") => dxf::save(mesh, &args.dxf(), &args.output)?,
+        Some("glb") | Some("gltf") => gltf::save(mesh, &args.gltf(), &args.output)?,
+        Some("stl") => {
+            let map = match &args.height_map {
+                Some(path) => Some(image::imageops::grayscale(&open_image(path)?)),
+                None => None,
+            };
+            stl::save(mesh, &args.stl(map), &args.output)?
+        }
+        _ => {
+            return Err(Error::Config {
+                path: None,
+                message: format!(
+                "can not export to {}, expected a .svg, .pdf, .obj, .ply, .glb, .gltf, .stl, .dxf or .html file",
+                args.output.display()
+            ),
+            })
+        }
+    }
+    println!("exported to {}", args.output.display());
+    Ok(())
+}
+
+fn encode(args: EncodeArgs) -> Result<()> {
+    let mesh = Mesh::load(&args.mesh)?;
+    let data = codec::encode(&mesh, &args.codec());
+    std::fs::write(&args.output, &data).map_err(|e| Error::io(&args.output, e))?;
+    let pixels = (mesh.width * mesh.height) as f64;
+    println!(
+        "encoded to {}: {} bytes, {:.5} bytes per pixel ({:.4} bits)",
+        args.output.display(),
+        data.len(),
+        data.len() as f64 / pixels,
+        data.len() as f64 * 8.0 / pixels
+    );
+
+    let path = match &args.image {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    let image = open_image(path)?;
+    let decoded = codec::decode(&data)?;
+    let score = Score::images(&decoded.render(image.dimensions())?, &image);
+    println!("psnr: {:.3} dB", score.psnr);
+    // best JPEG quality that is not bigger than the encoded mesh
+    let (mut low, mut high) = (1, 100);
+    let mut best = None;
+    while low <= high {
+        let quality = (low + high) / 2;
+        let jpeg = encode_jpeg(&image, quality, path)?;
+        if jpeg.len() <= data.len() {
+            best = Some((quality, jpeg));
+            low = quality + 1;
+        } else {
+            high = quality - 1;
+        }
+    }
+    match best {
+        Some((quality, jpeg)) => {
+            let decoded = image::load_from_memory_with_format(&jpeg, image::ImageFormat::Jpeg)
+                .map_err(|e| Error::Decode {
+                    path: path.clone(),
+                    source: e,
+                })?
+                .to_rgb8();
+            println!(
+                "jpeg at quality {}: {} bytes, psnr {:.3} dB",
+                quality,
+                jpeg.len(),
+                Score::images(&decoded, &image).psnr
+            );
+        }
+        None => println!(
+            "jpeg: even quality 1 takes {} bytes",
+            encode_jpeg(&image, 1, path)?.len()
+        ),
+    }
+    Ok(())
+}
+
+fn encode_jpeg(image: &RgbImage, quality: u8, path: &Path) -> Result<Vec<u8>> {
+    let mut jpeg = vec![];
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, quality)
+        .encode(
+            image.as_raw(),
+            image.width(),
+            image.height(),
+            image::ColorType::Rgb8,
+        )
+        .map_err(|e| Error::Encode {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    Ok(jpeg)
+}
+
+fn decode(args: DecodeArgs) -> Result<()> {
+    let mesh = codec::load(&args.input)?;
+    mesh.save(&args.output)?;
+    println!(
+        "decoded {} points and {} faces to {}",
+        mesh.points.len(),
+        mesh.triangles.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn morph(args: MorphArgs) -> Result<()> {
+    let from = Mesh::load(&args.from)?;
+    let to = Mesh::load(&args.to)?;
+    let size = args.size((to.width, to.height))?;
+    let morph = Morph::new(&from, &to);
+    let meshes = std::iter::once(from.clone())
+        .chain(morph.frames(args.frames))
+        .chain(std::iter::once(to.clone()));
+    let output = &args.output;
+    match animation::Format::from_path(output) {
+        Some(format) => {
+            let mut file = BufWriter::new(File::create(output).map_err(|e| Error::io(output, e))?);
+            let mut animation = Animation::new(&mut file, format, size, args.timing())
+                .map_err(|e| Error::io(output, e))?;
+            for mesh in meshes {
+                animation
+                    .push(mesh.render(size)?)
+                    .map_err(|e| Error::io(output, e))?;
+            }
+            animation.finish().map_err(|e| Error::io(output, e))?;
+            file.flush().map_err(|e| Error::io(output, e))?;
+        }
+        None if output.extension().is_none() => {
+            std::fs::create_dir_all(output).map_err(|e| Error::io(output, e))?;
+            for (i, mesh) in meshes.enumerate() {
+                let path = output.join(format!("frame-{:04}.png", i));
+                save_image(&mesh.render(size)?, &path, image::ImageFormat::Png)?;
+            }
+        }
+        None => {
+            return Err(Error::Config {
+                path: None,
+                message: format!(
+                    "can not morph to {}, expected a .gif, .png or .apng file or a directory",
+                    output.display()
+                ),
+            })
+        }
+    }
+    println!("written {} frames to {}", args.frames + 2, output.display());
+    Ok(())
+}
 
+fn numbers(args: NumbersArgs) -> Result<()> {
+    let mesh = Mesh::load(&args.mesh)?;
+    let kit = numbers::Kit::new(&mesh, &args.numbers());
+    let dir = &args.output;
+    std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, e))?;
+    kit.save_sheet(&dir.join("sheet.svg"))?;
+    kit.save_legend(&dir.join("legend.svg"))?;
+    let png = image::ImageFormat::Png;
+    save_image(&kit.render_sheet(args.width), &dir.join("sheet.png"), png)?;
+    save_image(&kit.render_legend(args.width), &dir.join("legend.png"), png)?;
+    save_image(
+        &kit.render_painting(args.width),
+        &dir.join("painted.png"),
+        png,
+    )?;
+    println!(
+        "{} regions in {} colors written to {}",
+        kit.regions(),
+        kit.palette().len(),
+        dir.display()
+    );
     Ok(())
 }
@@ -1,3 +1,4 @@
+use super::config::MemberConfig;
 use super::geom::Point;
 
 use rand::prelude::*;
@@ -5,9 +6,6 @@ use rand_distr::StandardNormal;
 use std::cell::RefCell;
 use std::rc::Rc;
 
-const MAX_MUTATION_VALUE: f32 = 5.0;
-const MUTATION_FREQUENCY: f32 = 3.0 / 5.0;
-
 #[derive(Debug)]
 pub struct Member {
     pub id: usize,
@@ -17,6 +15,7 @@ pub struct Member {
     size: usize,
     dimensions: (f32, f32),
     n_points: f32,
+    config: MemberConfig,
     pub fitness: f32,
 }
 
@@ -27,6 +26,7 @@ impl Member {
         point: (f32, f32),
         dimensions: (f32, f32),
         n_points: f32,
+        config: MemberConfig,
     ) -> Member {
         Member {
             id,
@@ -41,6 +41,7 @@ impl Member {
             size: 0,
             dimensions,
             n_points,
+            config,
             fitness: 0.0,
         }
     }
@@ -53,19 +54,22 @@ impl Member {
             size: 0,
             dimensions: self.dimensions,
             n_points: self.n_points,
+            config: self.config,
             fitness: 0.0,
         }
     }
-    pub fn mutate(&mut self) -> Rc<RefCell<Member>> {
+    pub fn mutate<R: Rng>(&mut self, rng: &mut R) -> Rc<RefCell<Member>> {
         self.size += 1;
-        if should_mutate(MUTATION_FREQUENCY) {
-            let random_point = Point::new(random(), random());
+        if should_mutate(self.config.mutation_frequency, rng) {
+            let scale = self.config.max_mutation_value;
+            let random_point = Point::new(random(scale, rng), random(scale, rng));
             let mutation = Rc::new(RefCell::new(Member::new(
                 self.id,
                 MemberType::Mutation(random_point),
                 self.point.values(),
                 self.dimensions,
                 self.n_points,
+                self.config,
             )));
             self.mutations.push(Rc::clone(&mutation));
             mutation
@@ -97,12 +101,13 @@ impl Member {
             aggregate.values(),
             self.dimensions,
             self.n_points,
+            self.config,
         )));
         self.mutations.push(Rc::clone(&mutation));
         self.size += 1;
         mutation
     }
-    pub fn add_fitness(&mut self, fitness: f32) -> () {
+    pub fn add_fitness(&mut self, fitness: f32) {
         self.fitness += fitness;
     }
 }
@@ -122,12 +127,12 @@ impl Clone for MemberType {
     }
 }
 
-fn should_mutate(rate: f32) -> bool {
-    thread_rng().gen_bool(rate as f64)
+fn should_mutate<R: Rng>(rate: f32, rng: &mut R) -> bool {
+    rng.gen_bool(rate as f64)
 }
 
-fn random() -> f32 {
-    let val: f32 = thread_rng().sample(StandardNormal);
+fn random<R: Rng>(scale: f32, rng: &mut R) -> f32 {
+    let val: f32 = rng.sample(StandardNormal);
     // val * 5.0 + 1.0
-    (val - 0.5) * MAX_MUTATION_VALUE
+    (val - 0.5) * scale
 }
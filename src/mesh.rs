@@ -0,0 +1,303 @@
+use super::config::{Config, MemberConfig};
+use super::error::{Error, Result};
+use super::face::Face;
+use super::file;
+use super::generation::Population;
+use super::geom::{contains, Point, Triangle};
+use super::member::{Member, MemberType};
+use super::render;
+
+use image::RgbImage;
+use serde::{Deserialize, Serialize};
+use spade::delaunay::FloatDelaunayTriangulation;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+// Version written to the `version` field, older files have no field
+pub(crate) const FORMAT_VERSION: u32 = 1;
+
+/// An evolved triangulation of an image.
+///
+/// Saved as JSON, the format is described in `docs/mesh-format.md` and
+/// `docs/mesh.schema.json`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Mesh {
+    /// Version of the file format.
+    #[serde(default = "format_version")]
+    pub version: u32,
+    /// Width of the image the mesh was evolved on.
+    pub width: u32,
+    /// Height of the image the mesh was evolved on.
+    pub height: u32,
+    /// Vertices in image coordinates.
+    pub points: Vec<(f32, f32)>,
+    /// Indices into `points` of the vertices of every triangle.
+    pub triangles: Vec<[usize; 3]>,
+    /// RGB color of every triangle.
+    pub colors: Vec<[u8; 3]>,
+    /// Fitness of every triangle, empty when unknown.
+    #[serde(default)]
+    pub fitness: Vec<f32>,
+    /// Run the mesh was evolved in, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<RunInfo>,
+    /// Index of every point in the points of the mesh it was evolved from,
+    /// empty when unknown.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub origins: Vec<usize>,
+}
+
+/// Run metadata saved with a mesh.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunInfo {
+    /// Effective configuration of the run.
+    pub config: Config,
+    /// Seed of the run.
+    pub seed: u32,
+    /// Generation of the run the mesh is the result of.
+    pub generation: u32,
+}
+
+impl Mesh {
+    pub(crate) fn new(population: &Population, (width, height): (f32, f32)) -> Mesh {
+        let mut triangles = vec![];
+        let mut colors = vec![];
+        let mut fitness = vec![];
+        for face in &population.faces {
+            triangles.push([
+                face.points.0.borrow().id,
+                face.points.1.borrow().id,
+                face.points.2.borrow().id,
+            ]);
+            colors.push(face.color.0);
+            fitness.push(face.fitness);
+        }
+        Mesh {
+            version: FORMAT_VERSION,
+            width: width as u32,
+            height: height as u32,
+            points: population.points.clone(),
+            triangles,
+            colors,
+            fitness,
+            run: None,
+            origins: population.origins.clone(),
+        }
+    }
+    /// Dimensions of the image the mesh was evolved on.
+    pub fn dimensions(&self) -> (f32, f32) {
+        (self.width as f32, self.height as f32)
+    }
+    /// Area in pixels of the triangle at `index`.
+    pub fn triangle_area(&self, index: usize) -> f32 {
+        let [a, b, c] = self.triangles[index];
+        Triangle::from_points(
+            Point::from(self.points[a]),
+            Point::from(self.points[b]),
+            Point::from(self.points[c]),
+        )
+        .area()
+    }
+    // Color of the face containing `point`, or of the closest face
+    pub(crate) fn color_at(&self, point: (f32, f32)) -> [u8; 3] {
+        let mut closest = (f32::INFINITY, [0, 0, 0]);
+        for (t, color) in self.triangles.iter().zip(&self.colors) {
+            let [a, b, c] = t.map(|i| self.points[i]);
+            if contains([a, b, c], point) {
+                return *color;
+            }
+            let center = ((a.0 + b.0 + c.0) / 3.0, (a.1 + b.1 + c.1) / 3.0);
+            let distance = (center.0 - point.0).powi(2) + (center.1 - point.1).powi(2);
+            if distance < closest.0 {
+                closest = (distance, *color);
+            }
+        }
+        closest.1
+    }
+    /// Rasterize the mesh into an image of `size`, scaling it from the
+    /// dimensions it was evolved on.
+    pub fn render(&self, size: (u32, u32)) -> Result<RgbImage> {
+        self.validate()?;
+        Ok(render::rasterize(self, size))
+    }
+    // Rebuild the population, faces keep their saved colors and fitness
+    pub(crate) fn population(&self, config: MemberConfig) -> Result<Population> {
+        self.validate()?;
+        let members: Vec<Rc<RefCell<Member>>> = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(id, p)| {
+                Rc::new(RefCell::new(Member::new(
+                    id,
+                    MemberType::Base,
+                    *p,
+                    self.dimensions(),
+                    self.points.len() as f32,
+                    config,
+                )))
+            })
+            .collect();
+
+        let mut faces = vec![];
+        for (i, ([a, b, c], color)) in self.triangles.iter().zip(&self.colors).enumerate() {
+            let vertices = (
+                Rc::clone(&members[*a]),
+                Rc::clone(&members[*b]),
+                Rc::clone(&members[*c]),
+            );
+            let triangle = Triangle::from_points(
+                Point::from(self.points[*a]),
+                Point::from(self.points[*b]),
+                Point::from(self.points[*c]),
+            );
+            let mut face = Face::with_color(triangle, vertices, image::Rgb(*color));
+            face.fitness = self.fitness.get(i).copied().unwrap_or(0.0);
+            faces.push(face);
+        }
+
+        Ok(Population::new(faces, self.points.clone()))
+    }
+    /// Read a mesh saved with [`Mesh::save`].
+    pub fn load(path: &Path) -> Result<Mesh> {
+        let contents = std::fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let mesh: Mesh = serde_json::from_str(&contents)
+            .map_err(|e| Error::mesh(e.to_string()).in_file(path))?;
+        mesh.validate().map_err(|e| e.in_file(path))?;
+        Ok(mesh)
+    }
+    /// Write the mesh as JSON.
+    ///
+    /// Like [`Checkpoint::save`](crate::Checkpoint::save), the file is
+    /// replaced whole, never left half written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents =
+            serde_json::to_string(self).map_err(|e| Error::mesh(e.to_string()).in_file(path))?;
+        file::replace(path, contents.as_bytes())
+    }
+    fn validate(&self) -> Result<()> {
+        if self.version > FORMAT_VERSION {
+            return Err(Error::mesh(format!(
+                "format version {} is newer than the supported version {}",
+                self.version, FORMAT_VERSION
+            )));
+        }
+        if self.width == 0 || self.height == 0 {
+            return Err(Error::mesh(format!(
+                "mesh dimensions {}x{} are empty",
+                self.width, self.height
+            )));
+        }
+        if !self.fitness.is_empty() && self.fitness.len() != self.triangles.len() {
+            return Err(Error::mesh(format!(
+                "mesh has {} triangles but {} fitness values",
+                self.triangles.len(),
+                self.fitness.len()
+            )));
+        }
+        if let Some(run) = &self.run {
+            run.config.validate()?;
+        }
+        if !self.origins.is_empty() && self.origins.len() != self.points.len() {
+            return Err(Error::mesh(format!(
+                "mesh has {} points but {} origins",
+                self.points.len(),
+                self.origins.len()
+            )));
+        }
+        if self.triangles.len() != self.colors.len() {
+            return Err(Error::mesh(format!(
+                "mesh has {} triangles but {} colors",
+                self.triangles.len(),
+                self.colors.len()
+            )));
+        }
+        for indices in &self.triangles {
+            if indices.iter().any(|i| *i >= self.points.len()) {
+                return Err(Error::mesh(format!(
+                    "triangle {:?} refers to a missing point, mesh has {} points",
+                    indices,
+                    self.points.len()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Faces of a mesh bucketed into a grid of cells by their bounding boxes, to
+// find the face at a point without testing every face
+pub(crate) struct FaceGrid<'a> {
+    mesh: &'a Mesh,
+    cell: f32,
+    columns: usize,
+    rows: usize,
+    // faces whose bounding box overlaps each cell, in mesh order
+    cells: Vec<Vec<usize>>,
+}
+
+impl<'a> FaceGrid<'a> {
+    pub(crate) fn new(mesh: &'a Mesh) -> FaceGrid<'a> {
+        // about a face per cell
+        let area = mesh.width as f32 * mesh.height as f32;
+        let cell = (area / mesh.triangles.len().max(1) as f32).sqrt().max(1.0);
+        let columns = (mesh.width as f32 / cell) as usize + 1;
+        let rows = (mesh.height as f32 / cell) as usize + 1;
+        let mut grid = FaceGrid {
+            mesh,
+            cell,
+            columns,
+            rows,
+            cells: vec![vec![]; columns * rows],
+        };
+        for (i, t) in mesh.triangles.iter().enumerate() {
+            let [a, b, c] = t.map(|i| mesh.points[i]);
+            let (left, top) = grid.position((a.0.min(b.0).min(c.0), a.1.min(b.1).min(c.1)));
+            let (right, bottom) = grid.position((a.0.max(b.0).max(c.0), a.1.max(b.1).max(c.1)));
+            for row in top..=bottom {
+                for column in left..=right {
+                    grid.cells[row * columns + column].push(i);
+                }
+            }
+        }
+        grid
+    }
+    // Same as Mesh::color_at
+    pub(crate) fn color_at(&self, point: (f32, f32)) -> [u8; 3] {
+        let (column, row) = self.position(point);
+        for i in &self.cells[row * self.columns + column] {
+            let t = self.mesh.triangles[*i].map(|i| self.mesh.points[i]);
+            if contains(t, point) {
+                return self.mesh.colors[*i];
+            }
+        }
+        // outside every face
+        self.mesh.color_at(point)
+    }
+    // Cell of `point`, points outside the grid fall in the cells on its edge
+    fn position(&self, (x, y): (f32, f32)) -> (usize, usize) {
+        let clamp = |v: f32, n: usize| ((v / self.cell).max(0.0) as usize).min(n - 1);
+        (clamp(x, self.columns), clamp(y, self.rows))
+    }
+}
+
+fn format_version() -> u32 {
+    FORMAT_VERSION
+}
+
+// Delaunay triangulation of `points` as indices, in the order spade yields the faces
+pub(crate) fn triangulate(points: &[(f32, f32)]) -> Vec<[usize; 3]> {
+    let mut delaunay = FloatDelaunayTriangulation::with_walk_locate();
+    // a point inserted twice keeps the first index
+    let mut indices = HashMap::new();
+    for (i, point) in points.iter().enumerate() {
+        let handle = delaunay.insert(Point::from(*point));
+        indices.entry(handle).or_insert(i);
+    }
+    delaunay
+        .triangles()
+        .map(|face| face.as_triangle().map(|vertex| indices[&vertex.fix()]))
+        .collect()
+}
@@ -0,0 +1,162 @@
+//! Record of how a render was made.
+//!
+//! PNG renders carry it in text chunks, renders in other formats in a JSON
+//! sidecar next to them, named after the render with `.json` appended. Either
+//! is enough to trace an image back to its input and evolve it again.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::mesh::Mesh;
+use crate::png;
+
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageFormat, RgbImage};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+// Keywords of the PNG text chunks, the software is informative only
+const SOFTWARE: &str = "Software";
+const INPUT: &str = "traingle:input";
+const IMAGE_SHA256: &str = "traingle:image-sha256";
+const SEED: &str = "traingle:seed";
+const GENERATION: &str = "traingle:generation";
+const FITNESS: &str = "traingle:fitness";
+const CONFIG: &str = "traingle:config";
+
+/// How a render was made.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Metadata {
+    /// File name of the input image.
+    pub input: String,
+    /// SHA-256 of the input file, in hex.
+    pub image_sha256: String,
+    /// Seed of the run.
+    pub seed: u32,
+    /// Generation the render is of.
+    pub generation: u32,
+    /// Sum of the fitness of every face of the generation.
+    pub fitness: f32,
+    /// Effective configuration of the run.
+    pub config: Config,
+}
+
+impl Metadata {
+    /// Metadata of a render of `mesh`, evolved from the image file `input`
+    /// whose SHA-256 is `image_sha256`. None when the mesh doesn't record
+    /// the run it comes from.
+    pub fn new(input: &Path, image_sha256: &str, mesh: &Mesh) -> Option<Metadata> {
+        let run = mesh.run.as_ref()?;
+        let input = input
+            .file_name()
+            .unwrap_or(input.as_os_str())
+            .to_string_lossy()
+            .into_owned();
+        Some(Metadata {
+            input,
+            image_sha256: image_sha256.to_string(),
+            seed: run.seed,
+            generation: run.generation,
+            fitness: mesh.fitness.iter().sum(),
+            config: run.config.clone(),
+        })
+    }
+    /// SHA-256 of the contents of `path`, in hex.
+    pub fn hash_file(path: &Path) -> Result<String> {
+        let contents = std::fs::read(path).map_err(|e| Error::io(path, e))?;
+        Ok(Sha256::digest(&contents)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect())
+    }
+    /// Sidecar of the render at `path`, `path` with `.json` appended.
+    pub fn sidecar(path: &Path) -> PathBuf {
+        let mut name = OsString::from(path.as_os_str());
+        name.push(".json");
+        PathBuf::from(name)
+    }
+    /// Save `image` to `path` in `format` with the metadata, in text chunks
+    /// for PNG and in the sidecar otherwise.
+    pub fn save_image(&self, image: &RgbImage, path: &Path, format: ImageFormat) -> Result<()> {
+        let encode = |source| Error::Encode {
+            path: path.to_path_buf(),
+            source,
+        };
+        if format != ImageFormat::Png {
+            image.save_with_format(path, format).map_err(encode)?;
+            let sidecar = Metadata::sidecar(path);
+            let contents = serde_json::to_string_pretty(self)
+                .map_err(|e| Error::metadata(e.to_string()).in_file(&sidecar))?;
+            return std::fs::write(&sidecar, contents).map_err(|e| Error::io(&sidecar, e));
+        }
+        let mut encoded = vec![];
+        PngEncoder::new(&mut encoded)
+            .encode(image, image.width(), image.height(), ColorType::Rgb8)
+            .map_err(encode)?;
+        let config = toml::to_string_pretty(&self.config)
+            .map_err(|e| Error::metadata(e.to_string()).in_file(path))?;
+        let entries = [
+            (SOFTWARE, format!("traingle {}", env!("CARGO_PKG_VERSION"))),
+            (INPUT, self.input.clone()),
+            (IMAGE_SHA256, self.image_sha256.clone()),
+            (SEED, self.seed.to_string()),
+            (GENERATION, self.generation.to_string()),
+            (FITNESS, self.fitness.to_string()),
+            (CONFIG, config),
+        ];
+        let png = png::insert_text(&encoded, &entries).map_err(|e| Error::io(path, e))?;
+        std::fs::write(path, png).map_err(|e| Error::io(path, e))
+    }
+    /// Read the metadata of the render at `path`, from its text chunks when
+    /// it's a PNG that has them and otherwise from its sidecar. None when it
+    /// has neither.
+    pub fn load(path: &Path) -> Result<Option<Metadata>> {
+        if ImageFormat::from_path(path).ok() == Some(ImageFormat::Png) {
+            let contents = std::fs::read(path).map_err(|e| Error::io(path, e))?;
+            let entries = png::read_text(&contents).map_err(|e| Error::io(path, e))?;
+            if let Some(metadata) = from_text(&entries).map_err(|e| e.in_file(path))? {
+                return Ok(Some(metadata));
+            }
+        }
+        let sidecar = Metadata::sidecar(path);
+        if !sidecar.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&sidecar).map_err(|e| Error::io(&sidecar, e))?;
+        serde_json::from_str(&contents)
+            .map(Some)
+            .map_err(|e| Error::metadata(e.to_string()).in_file(&sidecar))
+    }
+}
+
+// Metadata of the text chunks of a PNG, None when they have none
+fn from_text(entries: &[(String, String)]) -> Result<Option<Metadata>> {
+    if !entries.iter().any(|(keyword, _)| keyword == INPUT) {
+        return Ok(None);
+    }
+    Ok(Some(Metadata {
+        input: text(entries, INPUT)?.to_string(),
+        image_sha256: text(entries, IMAGE_SHA256)?.to_string(),
+        seed: number(entries, SEED)?,
+        generation: number(entries, GENERATION)?,
+        fitness: number(entries, FITNESS)?,
+        config: toml::from_str(text(entries, CONFIG)?)
+            .map_err(|e| Error::metadata(format!("{}: {}", CONFIG, e)))?,
+    }))
+}
+
+fn text<'a>(entries: &'a [(String, String)], keyword: &str) -> Result<&'a str> {
+    entries
+        .iter()
+        .find(|(k, _)| k == keyword)
+        .map(|(_, text)| text.as_str())
+        .ok_or_else(|| Error::metadata(format!("missing {}", keyword)))
+}
+
+fn number<T: FromStr>(entries: &[(String, String)], keyword: &str) -> Result<T> {
+    let text = text(entries, keyword)?;
+    text.parse()
+        .map_err(|_| Error::metadata(format!("{} is not a number: {}", keyword, text)))
+}
@@ -0,0 +1,114 @@
+use super::mesh::{self, Mesh};
+
+// Start and end of a point
+type Track = ((f32, f32), (f32, f32));
+
+/// In-between meshes of a morph from one mesh to another.
+///
+/// Every point travels in a straight line from `from` to `to` and each frame
+/// is triangulated again, its faces blending the colors both meshes have
+/// where they lie.
+pub struct Morph {
+    from: Mesh,
+    to: Mesh,
+    // scale of `from` onto the dimensions of `to`
+    scale: (f32, f32),
+    // start and end of every point, in the dimensions of `to`
+    tracks: Vec<Track>,
+}
+
+impl Morph {
+    /// Morph `from` into `to`. When `to` was evolved from `from` its points
+    /// start where they evolved from, recorded in `to.origins`; otherwise,
+    /// as between meshes of two images, at the closest point of `from`,
+    /// stretched over `to`. Points of `from` that `to` doesn't have travel
+    /// to the closest point of `to`.
+    pub fn new(from: &Mesh, to: &Mesh) -> Morph {
+        let scale = (
+            to.width as f32 / from.width as f32,
+            to.height as f32 / from.height as f32,
+        );
+        let starts: Vec<(f32, f32)> = from
+            .points
+            .iter()
+            .map(|(x, y)| (x * scale.0, y * scale.1))
+            .collect();
+        let evolved = from.dimensions() == to.dimensions()
+            && to.origins.len() == to.points.len()
+            && to.origins.iter().all(|i| *i < from.points.len());
+
+        let mut tracks = vec![];
+        let mut used = vec![false; starts.len()];
+        for (j, end) in to.points.iter().enumerate() {
+            let start = if evolved {
+                Some(to.origins[j])
+            } else {
+                closest(&starts, *end)
+            };
+            if let Some(i) = start {
+                used[i] = true;
+                tracks.push((starts[i], *end));
+            }
+        }
+        for (start, _) in starts.iter().zip(used).filter(|(_, used)| !used) {
+            if let Some(j) = closest(&to.points, *start) {
+                tracks.push((*start, to.points[j]));
+            }
+        }
+        Morph {
+            from: from.clone(),
+            to: to.clone(),
+            scale,
+            tracks,
+        }
+    }
+    /// Mesh at `t`, from 0 at `from` to 1 at `to`, with the dimensions of
+    /// `to`.
+    pub fn at(&self, t: f32) -> Mesh {
+        let lerp = |a: f32, b: f32| a + (b - a) * t;
+        let points: Vec<(f32, f32)> = self
+            .tracks
+            .iter()
+            .map(|((x0, y0), (x1, y1))| (lerp(*x0, *x1), lerp(*y0, *y1)))
+            .collect();
+        let triangles = mesh::triangulate(&points);
+        let colors = triangles
+            .iter()
+            .map(|triangle| {
+                let center = |end: fn(&Track) -> (f32, f32)| {
+                    let [a, b, c] = triangle.map(|i| end(&self.tracks[i]));
+                    ((a.0 + b.0 + c.0) / 3.0, (a.1 + b.1 + c.1) / 3.0)
+                };
+                let (x, y) = center(|track| track.0);
+                let from = self.from.color_at((x / self.scale.0, y / self.scale.1));
+                let to = self.to.color_at(center(|track| track.1));
+                [0, 1, 2].map(|i| lerp(from[i] as f32, to[i] as f32).round() as u8)
+            })
+            .collect();
+        Mesh {
+            version: mesh::FORMAT_VERSION,
+            width: self.to.width,
+            height: self.to.height,
+            points,
+            triangles,
+            colors,
+            fitness: vec![],
+            run: None,
+            origins: vec![],
+        }
+    }
+    /// `n` meshes evenly spaced between `from` and `to`, without either.
+    pub fn frames(&self, n: u32) -> impl Iterator<Item = Mesh> + '_ {
+        (1..=n).map(move |i| self.at(i as f32 / (n + 1) as f32))
+    }
+}
+
+// Index of the point of `points` closest to `point`
+fn closest(points: &[(f32, f32)], point: (f32, f32)) -> Option<usize> {
+    points
+        .iter()
+        .map(|(x, y)| (x - point.0).powi(2) + (y - point.1).powi(2))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+}
@@ -0,0 +1,52 @@
+use super::mesh::Mesh;
+use super::triangulator::Stop;
+
+use std::time::Duration;
+
+/// Summary of an evolved generation.
+#[derive(Debug, Clone, Copy)]
+pub struct Stats {
+    /// Number of the generation.
+    pub generation: u32,
+    /// Average fitness of the members picked for the generation.
+    pub average_fitness: f32,
+    /// Sum of the fitness of every face.
+    pub total_fitness: f32,
+    /// Lowest fitness of a face.
+    pub min_face_fitness: f32,
+    /// Highest fitness of a face.
+    pub max_face_fitness: f32,
+    /// PSNR in dB of the render of the generation against the image.
+    pub psnr: f64,
+    /// Whether the run continues from this generation, see
+    /// [`Acceptance`](crate::config::Acceptance).
+    pub accepted: bool,
+    /// Number of points.
+    pub points: usize,
+    /// Number of faces.
+    pub faces: usize,
+    /// Time spent evolving the generation.
+    pub elapsed: Duration,
+}
+
+/// Receives the progress of an [`Evolution`](crate::Evolution), see
+/// [`Evolution::observe`](crate::Evolution::observe).
+///
+/// Every method does nothing by default.
+pub trait Observer {
+    /// The engine starts evolving `generation`.
+    fn generation_started(&mut self, _generation: u32) {}
+    /// A generation was evolved. `mesh` is the generation the run continues
+    /// from and the iterator yields, the previous one when the new generation
+    /// was not accepted.
+    fn generation_finished(&mut self, _stats: &Stats, _mesh: &Mesh) {}
+    /// `mesh` has the best PSNR so far.
+    fn best_changed(&mut self, _generation: u32, _mesh: &Mesh) {}
+    /// The run ended, no generation follows.
+    fn stopped(&mut self, _stop: Stop) {}
+    /// Return true to stop the run. Checked before every generation and
+    /// between mutation rounds, a generation cancelled halfway is dropped.
+    fn cancelled(&mut self) -> bool {
+        false
+    }
+}
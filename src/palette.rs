@@ -0,0 +1,119 @@
+// Reduce colors to a palette with weighted k-means
+
+use super::mesh::Mesh;
+
+use std::collections::HashMap;
+
+// Palette of at most `k` colors and the palette index of every color.
+// Colors with a bigger weight pull the palette towards them.
+pub fn reduce(colors: &[[u8; 3]], weights: &[f32], k: usize) -> (Vec<[u8; 3]>, Vec<usize>) {
+    let k = k.max(1);
+    // distinct colors in order of appearance with their total weight
+    let mut distinct: Vec<([u8; 3], f32)> = vec![];
+    let mut seen: HashMap<[u8; 3], usize> = HashMap::new();
+    for (color, weight) in colors.iter().zip(weights) {
+        let weight = weight.max(f32::MIN_POSITIVE);
+        match seen.get(color) {
+            Some(&i) => distinct[i].1 += weight,
+            None => {
+                seen.insert(*color, distinct.len());
+                distinct.push((*color, weight));
+            }
+        }
+    }
+    if distinct.len() <= k {
+        let palette: Vec<[u8; 3]> = distinct.iter().map(|(c, _)| *c).collect();
+        let indices = colors.iter().map(|c| nearest(&palette, *c)).collect();
+        return (palette, indices);
+    }
+
+    // start from the heaviest color, then repeatedly the one farthest from
+    // the palette, so the result doesn't depend on chance
+    let heaviest = distinct
+        .iter()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(c, _)| *c)
+        .unwrap_or_default();
+    let mut centers: Vec<[f32; 3]> = vec![to_f32(heaviest)];
+    while centers.len() < k {
+        let farthest = distinct
+            .iter()
+            .map(|(c, w)| {
+                let d = centers
+                    .iter()
+                    .map(|center| distance(center, &to_f32(*c)))
+                    .fold(f32::INFINITY, f32::min);
+                (*c, d * w)
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(c, _)| c)
+            .unwrap_or_default();
+        centers.push(to_f32(farthest));
+    }
+
+    for _ in 0..20 {
+        let mut sums = vec![([0.0f32; 3], 0.0f32); k];
+        for (color, weight) in &distinct {
+            let c = to_f32(*color);
+            let i = nearest_center(&centers, &c);
+            for (sum, channel) in sums[i].0.iter_mut().zip(c) {
+                *sum += channel * weight;
+            }
+            sums[i].1 += weight;
+        }
+        let mut moved = false;
+        for (center, (sum, weight)) in centers.iter_mut().zip(&sums) {
+            if *weight > 0.0 {
+                let next = sum.map(|s| s / weight);
+                moved |= distance(center, &next) > 0.25;
+                *center = next;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    let palette: Vec<[u8; 3]> = centers
+        .iter()
+        .map(|c| c.map(|channel| channel.round().clamp(0.0, 255.0) as u8))
+        .collect();
+    let indices = colors.iter().map(|c| nearest(&palette, *c)).collect();
+    (palette, indices)
+}
+
+// `mesh` with the faces recolored from a palette of at most `k` colors,
+// weighted by face area, and that palette
+pub fn quantize(mesh: &Mesh, k: usize) -> (Mesh, Vec<[u8; 3]>) {
+    let areas: Vec<f32> = (0..mesh.triangles.len())
+        .map(|i| mesh.triangle_area(i))
+        .collect();
+    let (palette, indices) = reduce(&mesh.colors, &areas, k);
+    let mut quantized = mesh.clone();
+    quantized.colors = indices.iter().map(|i| palette[*i]).collect();
+    (quantized, palette)
+}
+
+// Index of the palette color closest to `color`
+pub fn nearest(palette: &[[u8; 3]], color: [u8; 3]) -> usize {
+    let centers: Vec<[f32; 3]> = palette.iter().map(|c| to_f32(*c)).collect();
+    nearest_center(&centers, &to_f32(color))
+}
+
+fn nearest_center(centers: &[[f32; 3]], color: &[f32; 3]) -> usize {
+    let mut best = 0;
+    for (i, center) in centers.iter().enumerate() {
+        if distance(center, color) < distance(&centers[best], color) {
+            best = i;
+        }
+    }
+    best
+}
+
+fn to_f32(color: [u8; 3]) -> [f32; 3] {
+    color.map(|c| c as f32)
+}
+
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum()
+}
@@ -1,15 +1,4 @@
-// max distance used to group pixels into bins
-const BENEFICIAL_DISTANCE: f32 = 150.0;
-
-// if main bin contains less than this percent of total pixels,
-//  pixels are used from multiple bins to calculate color/fitness
-const MIN_PIXEL_PERCENT: f32 = 0.95;
-
-// bin must contain this percent of total pixels to be considered substantial
-const SUBSTANTIAL_BIN_AREA_PERCENT_THRESHOLD: f32 = 0.01;
-
-// entire group must contain this many pixels to have fitness
-const TOTAL_GROUP_SIZE_THRESHOLD: i32 = 10;
+use super::config::GroupConfig;
 
 pub struct Group {
     pub color: image::Rgb<u8>,
@@ -17,7 +6,7 @@ pub struct Group {
 }
 
 impl Group {
-    pub fn new<I>(pixels: &mut I) -> Group
+    pub fn new<I>(pixels: &mut I, config: &GroupConfig) -> Group
     where
         I: Iterator<Item = (f32, f32, f32)>,
     {
@@ -42,7 +31,7 @@ impl Group {
             for bin in &mut bins {
                 let dist = distance(bin.mean, pixel);
                 // if current pixel is close to this bin, push pixel into bin
-                if dist < BENEFICIAL_DISTANCE {
+                if dist < config.beneficial_distance {
                     // - bin.values.add - calculates moving mean, size
                     bin.add(pixel);
                     continue '_pixels;
@@ -55,7 +44,7 @@ impl Group {
         // sort bins by size descending
         bins.sort_by(|a, b| b.count.partial_cmp(&a.count).unwrap());
 
-        let min_pixel_count = (total as f32) * MIN_PIXEL_PERCENT;
+        let min_pixel_count = (total as f32) * config.min_pixel_percent;
         let color;
         let fitness;
         if (bins[0].count as f32) < min_pixel_count {
@@ -99,7 +88,7 @@ impl Group {
                 cumulative_distance_from_mean += distance(mean, pixel);
                 index += 1;
             }
-            fitness = Group::fitness(&bins, total, cumulative_distance_from_mean);
+            fitness = Group::fitness(&bins, total, cumulative_distance_from_mean, config);
             color = image::Rgb([mean.0 as u8, mean.1 as u8, mean.2 as u8]);
         } else {
             let mean = bins[0].mean;
@@ -107,17 +96,23 @@ impl Group {
             for pixel in &bins[0].values {
                 cumulative_distance_from_mean += distance(mean, *pixel);
             }
-            fitness = Group::fitness(&bins, total, cumulative_distance_from_mean);
+            fitness = Group::fitness(&bins, total, cumulative_distance_from_mean, config);
             color = image::Rgb([mean.0 as u8, mean.1 as u8, mean.2 as u8]);
         }
         Group { color, fitness }
     }
-    fn fitness(bins: &Vec<GroupBin>, total: i32, cumulative_distance_from_mean: f32) -> f32 {
-        if total < TOTAL_GROUP_SIZE_THRESHOLD {
+    fn fitness(
+        bins: &Vec<GroupBin>,
+        total: i32,
+        cumulative_distance_from_mean: f32,
+        config: &GroupConfig,
+    ) -> f32 {
+        if total < config.total_group_size_threshold {
             return 0.0;
         }
         let mut substantial_bins: i32 = 0;
-        let bin_threshold = ((total as f32) * SUBSTANTIAL_BIN_AREA_PERCENT_THRESHOLD) as i32;
+        let bin_threshold =
+            ((total as f32) * config.substantial_bin_area_percent_threshold) as i32;
         for bin in bins {
             if bin.count > bin_threshold {
                 substantial_bins += 1;
@@ -169,16 +164,12 @@ impl Group {
 impl Copy for Group {}
 impl Clone for Group {
     fn clone(&self) -> Group {
-        Group {
-            fitness: self.fitness,
-            color: self.color,
-        }
+        *self
     }
 }
 
 fn distance(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
-    let distance = (a.0 - b.0).powf(2.0) + (a.1 - b.1).powf(2.0) + (a.2 - b.2).powf(2.0);
-    distance
+    (a.0 - b.0).powf(2.0) + (a.1 - b.1).powf(2.0) + (a.2 - b.2).powf(2.0)
 }
 
 struct GroupBin {
@@ -195,7 +186,7 @@ impl GroupBin {
             values: vec![pixel],
         }
     }
-    fn add(&mut self, pixel: (f32, f32, f32)) -> () {
+    fn add(&mut self, pixel: (f32, f32, f32)) {
         self.values.push(pixel);
         self.count += 1;
         let delta = (
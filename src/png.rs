@@ -0,0 +1,116 @@
+// PNG chunks, for the APNG writer and the text chunks of run metadata
+
+use std::convert::TryFrom;
+use std::io::{self, Write};
+
+pub const SIGNATURE: &[u8; 8] = b"\x89PNG\r\n\x1a\n";
+
+pub fn chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    let length = u32::try_from(data.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "PNG chunk too large"))?;
+    out.write_all(&length.to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(crc32(0xffff_ffff, kind), data) ^ 0xffff_ffff;
+    out.write_all(&crc.to_be_bytes())
+}
+
+// `png` with a text chunk per entry after the header, tEXt when the text is
+// ASCII and UTF-8 iTXt otherwise
+pub fn insert_text(png: &[u8], entries: &[(&str, String)]) -> io::Result<Vec<u8>> {
+    let chunks = chunks(png)?;
+    let header = chunks
+        .first()
+        .filter(|(kind, _)| kind == b"IHDR")
+        .map(|(_, data)| data.len() + 12)
+        .ok_or_else(|| malformed("the PNG doesn't start with a header"))?;
+    let mut out = png[..SIGNATURE.len() + header].to_vec();
+    for (keyword, text) in entries {
+        let mut data = keyword.as_bytes().to_vec();
+        data.push(0);
+        if text.is_ascii() {
+            data.extend_from_slice(text.as_bytes());
+            chunk(&mut out, b"tEXt", &data)?;
+        } else {
+            // uncompressed, no language or translated keyword
+            data.extend_from_slice(&[0, 0, 0, 0]);
+            data.extend_from_slice(text.as_bytes());
+            chunk(&mut out, b"iTXt", &data)?;
+        }
+    }
+    out.extend_from_slice(&png[SIGNATURE.len() + header..]);
+    Ok(out)
+}
+
+// Keywords and texts of the tEXt and uncompressed iTXt chunks of `png`
+pub fn read_text(png: &[u8]) -> io::Result<Vec<(String, String)>> {
+    let mut entries = vec![];
+    for (kind, data) in chunks(png)? {
+        let (keyword, rest) = match data.iter().position(|b| *b == 0) {
+            Some(end) => (latin1(&data[..end]), &data[end + 1..]),
+            None => continue,
+        };
+        match &kind {
+            b"tEXt" => entries.push((keyword, latin1(rest))),
+            // compression flag and method, then the language tag and
+            // translated keyword, both ended by a zero
+            b"iTXt" if rest.len() >= 2 && rest[0] == 0 => {
+                let mut fields = rest[2..].splitn(3, |b| *b == 0);
+                if let (Some(_), Some(_), Some(text)) =
+                    (fields.next(), fields.next(), fields.next())
+                {
+                    let text = String::from_utf8(text.to_vec())
+                        .map_err(|_| malformed("iTXt chunk isn't UTF-8"))?;
+                    entries.push((keyword, text));
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(entries)
+}
+
+// Type and data of every chunk of `png`, up to IEND
+fn chunks(png: &[u8]) -> io::Result<Vec<([u8; 4], &[u8])>> {
+    if !png.starts_with(SIGNATURE) {
+        return Err(malformed("not a PNG file"));
+    }
+    let mut chunks = vec![];
+    let mut rest = &png[SIGNATURE.len()..];
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = [rest[4], rest[5], rest[6], rest[7]];
+        if rest.len() < length + 12 {
+            break;
+        }
+        chunks.push((kind, &rest[8..8 + length]));
+        rest = &rest[length + 12..];
+        if &kind == b"IEND" {
+            return Ok(chunks);
+        }
+    }
+    Err(malformed("the PNG is truncated"))
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| *b as char).collect()
+}
+
+fn malformed(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+// CRC-32 of PNG chunks, continued from `crc`
+fn crc32(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
@@ -0,0 +1,109 @@
+use super::mesh::Mesh;
+
+use std::collections::HashMap;
+
+// Connected faces of one color, merged into outlines
+pub struct Region {
+    pub color: [u8; 3],
+    pub faces: Vec<usize>,
+    // closed loops of point indices, holes wind the other way than the outer
+    // outline so they render with the nonzero fill rule
+    pub outlines: Vec<Vec<usize>>,
+}
+
+// Group faces sharing an edge and a color, in order of their first face
+pub fn regions(mesh: &Mesh) -> Vec<Region> {
+    let triangles: Vec<[usize; 3]> = mesh.triangles.iter().map(|t| orient(mesh, *t)).collect();
+
+    // faces on each side of every edge
+    let mut edges: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+    for (face, triangle) in triangles.iter().enumerate() {
+        for (a, b) in sides(*triangle) {
+            edges.entry(key(a, b)).or_default().push(face);
+        }
+    }
+
+    let mut region_of = vec![None; triangles.len()];
+    let mut regions = vec![];
+    for start in 0..triangles.len() {
+        if region_of[start].is_some() {
+            continue;
+        }
+        let color = mesh.colors[start];
+        let id = regions.len();
+        region_of[start] = Some(id);
+        let mut faces = vec![start];
+        let mut i = 0;
+        while i < faces.len() {
+            for (a, b) in sides(triangles[faces[i]]) {
+                for &neighbour in &edges[&key(a, b)] {
+                    if region_of[neighbour].is_none() && mesh.colors[neighbour] == color {
+                        region_of[neighbour] = Some(id);
+                        faces.push(neighbour);
+                    }
+                }
+            }
+            i += 1;
+        }
+        faces.sort_unstable();
+        regions.push(Region {
+            color,
+            faces,
+            outlines: vec![],
+        });
+    }
+
+    for (id, region) in regions.iter_mut().enumerate() {
+        // sides not shared with another face of the region, kept in face order
+        let mut next: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut boundary = vec![];
+        for &face in &region.faces {
+            for (a, b) in sides(triangles[face]) {
+                let inner = edges[&key(a, b)]
+                    .iter()
+                    .any(|&other| other != face && region_of[other] == Some(id));
+                if !inner {
+                    next.entry(a).or_default().push(b);
+                    boundary.push(a);
+                }
+            }
+        }
+        for start in boundary {
+            if next.get(&start).is_none_or(|ends| ends.is_empty()) {
+                continue;
+            }
+            // every boundary point has as many sides leaving as arriving, so
+            // walking gets back to the start unless a face has no area
+            let mut outline = vec![start];
+            let mut point = start;
+            while let Some(end) = next.get_mut(&point).and_then(|ends| ends.pop()) {
+                if end == start {
+                    break;
+                }
+                outline.push(end);
+                point = end;
+            }
+            region.outlines.push(outline);
+        }
+    }
+    regions
+}
+
+// Same winding for every triangle, so shared sides run in opposite directions
+fn orient(mesh: &Mesh, [a, b, c]: [usize; 3]) -> [usize; 3] {
+    let (pa, pb, pc) = (mesh.points[a], mesh.points[b], mesh.points[c]);
+    let cross = (pb.0 - pa.0) * (pc.1 - pa.1) - (pb.1 - pa.1) * (pc.0 - pa.0);
+    if cross < 0.0 {
+        [a, c, b]
+    } else {
+        [a, b, c]
+    }
+}
+
+fn sides([a, b, c]: [usize; 3]) -> [(usize, usize); 3] {
+    [(a, b), (b, c), (c, a)]
+}
+
+fn key(a: usize, b: usize) -> (usize, usize) {
+    (a.min(b), a.max(b))
+}
@@ -0,0 +1,15 @@
+use super::mesh::{FaceGrid, Mesh};
+
+use image::{ImageBuffer, Rgb, RgbImage};
+
+// Rasterize a mesh into an image of `size`, scaling it from the dimensions it
+//  was evolved on
+pub fn rasterize(mesh: &Mesh, (out_width, out_height): (u32, u32)) -> RgbImage {
+    let (width, height) = mesh.dimensions();
+    let scale_x = width / out_width as f32;
+    let scale_y = height / out_height as f32;
+    let grid = FaceGrid::new(mesh);
+    ImageBuffer::from_fn(out_width, out_height, |px, py| {
+        Rgb(grid.color_at((px as f32 * scale_x, py as f32 * scale_y)))
+    })
+}
@@ -0,0 +1,43 @@
+use image::RgbImage;
+
+/// How closely a mesh approximates an image, see [`Triangulator::score`].
+///
+/// [`Triangulator::score`]: crate::Triangulator::score
+#[derive(Debug, Clone, Copy)]
+pub struct Score {
+    /// Mean squared error per channel.
+    pub mse: f64,
+    /// Peak signal-to-noise ratio in dB, infinite for identical images.
+    pub psnr: f64,
+    /// Sum of the fitness of every face, when the mesh was evolved on an
+    /// image of the same dimensions.
+    pub fitness: Option<f32>,
+}
+
+impl Score {
+    /// Compare two images of the same dimensions, for example a render
+    /// against another compressed version of the original.
+    pub fn images(image: &RgbImage, original: &RgbImage) -> Score {
+        let (mse, psnr) = difference(image, original);
+        Score {
+            mse,
+            psnr,
+            fitness: None,
+        }
+    }
+}
+
+// Mean squared error and PSNR of two images with the same dimensions
+pub fn difference(rendered: &RgbImage, original: &RgbImage) -> (f64, f64) {
+    let mut sum = 0.0;
+    for (a, b) in rendered.pixels().zip(original.pixels()) {
+        for c in 0..3 {
+            let delta = a.0[c] as f64 - b.0[c] as f64;
+            sum += delta * delta;
+        }
+    }
+    let samples = (rendered.width() * rendered.height() * 3) as f64;
+    let mse = sum / samples;
+    let psnr = 10.0 * (255.0 * 255.0 / mse).log10();
+    (mse, psnr)
+}
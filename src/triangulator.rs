@@ -0,0 +1,518 @@
+use super::cache::Cache;
+use super::checkpoint::Checkpoint;
+use super::config::{Acceptance, Config};
+use super::error::{Error, Result};
+use super::generation::{Generation, Population};
+use super::img::Img;
+use super::mesh::{Mesh, RunInfo};
+use super::observer::{Observer, Stats};
+use super::score::{self, Score};
+
+use image::RgbImage;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Builder for a triangulation run.
+///
+/// Every option defaults to the value in [`Config::default`].
+#[derive(Debug, Clone, Default)]
+pub struct Triangulator {
+    config: Config,
+    interrupt: Option<Arc<AtomicBool>>,
+}
+
+/// Why an [`Evolution`] stopped yielding generations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stop {
+    /// Every generation of the run was evolved.
+    Generations,
+    /// The wall-clock budget ran out.
+    TimeLimit,
+    /// The average fitness did not improve for the configured number of
+    /// generations.
+    Plateau,
+    /// The average fitness reached the target.
+    TargetFitness,
+    /// The rendered mesh reached the target PSNR.
+    TargetPsnr,
+    /// The interrupt flag was set or an observer cancelled the run.
+    Interrupted,
+}
+
+impl Triangulator {
+    pub fn new() -> Triangulator {
+        Triangulator::default()
+    }
+    /// Replace every option with the values of `config`.
+    pub fn config(mut self, config: Config) -> Triangulator {
+        self.config = config;
+        self
+    }
+    /// Number of points along each side of the initial grid.
+    pub fn segments(mut self, segments: u32) -> Triangulator {
+        self.config.run.segments = segments;
+        self
+    }
+    /// Number of generations evolved after the initial one.
+    pub fn generations(mut self, generations: u32) -> Triangulator {
+        self.config.run.generations = generations;
+        self
+    }
+    /// Number of mutation rounds per generation.
+    pub fn mutations(mut self, mutations: u32) -> Triangulator {
+        self.config.run.mutations_per_generation = mutations;
+        self
+    }
+    /// Seed of the mutations, runs with the same image, options and seed
+    /// evolve identical meshes. A random seed is picked when not given.
+    pub fn seed(mut self, seed: u32) -> Triangulator {
+        self.config.run.seed = Some(seed);
+        self
+    }
+    /// Stop evolving once `limit` has passed since [`Triangulator::evolve`].
+    pub fn time_limit(mut self, limit: Duration) -> Triangulator {
+        self.config.stop.time_limit = Some(limit.as_secs_f64());
+        self
+    }
+    /// Stop evolving after `generations` generations in a row without a
+    /// better average fitness.
+    pub fn plateau(mut self, generations: u32) -> Triangulator {
+        self.config.stop.plateau = Some(generations);
+        self
+    }
+    /// Stop evolving once the average fitness reaches `fitness`.
+    pub fn target_fitness(mut self, fitness: f32) -> Triangulator {
+        self.config.stop.target_fitness = Some(fitness);
+        self
+    }
+    /// Stop evolving once the rendered mesh reaches `psnr` dB against the
+    /// image.
+    pub fn target_psnr(mut self, psnr: f64) -> Triangulator {
+        self.config.stop.target_psnr = Some(psnr);
+        self
+    }
+    /// Stop evolving once `flag` is set, for example from a Ctrl-C handler.
+    /// The flag is checked like [`Observer::cancelled`].
+    pub fn interrupt(mut self, flag: Arc<AtomicBool>) -> Triangulator {
+        self.interrupt = Some(flag);
+        self
+    }
+    /// Evolve a mesh for `image` and return the best generation, see
+    /// [`Evolution::best`].
+    ///
+    /// Fails with [`Error::Stopped`] when the run is interrupted or out of
+    /// time before the initial generation.
+    pub fn run(&self, image: &RgbImage) -> Result<Mesh> {
+        let mut evolution = self.evolve(image)?;
+        for mesh in evolution.by_ref() {
+            mesh?;
+        }
+        match evolution.best {
+            Some((_, mesh)) => Ok(mesh),
+            None => Err(Error::Stopped(evolution.stop.unwrap_or(Stop::Interrupted))),
+        }
+    }
+    /// Evolve a mesh for `image` one generation at a time.
+    ///
+    /// The iterator yields the initial generation followed by every evolved
+    /// generation, and stops after the first error.
+    pub fn evolve(&self, image: &RgbImage) -> Result<Evolution> {
+        self.config.validate()?;
+        let seed = match self.config.run.seed {
+            Some(seed) => seed,
+            None => rand::thread_rng().gen(),
+        };
+        let rng = ChaCha8Rng::seed_from_u64(seed as u64);
+        Ok(self.evolution(image, self.config.clone(), seed, rng))
+    }
+    /// Evolve a mesh for `image` starting from the points of `mesh` instead
+    /// of a grid, for example a mesh saved by an earlier run or edited by
+    /// hand.
+    ///
+    /// Generations are numbered on from the generation recorded in the mesh.
+    /// The options of this builder apply, pass the config recorded in
+    /// [`Mesh::run`] to keep those of the earlier run.
+    pub fn evolve_from(&self, image: &RgbImage, mesh: &Mesh) -> Result<Evolution> {
+        let mut evolution = self.evolve(image)?;
+        if (mesh.width, mesh.height) != image.dimensions() {
+            return Err(Error::mesh(format!(
+                "mesh was evolved on a {}x{} image, not {}x{}",
+                mesh.width,
+                mesh.height,
+                image.width(),
+                image.height()
+            )));
+        }
+        let previous = mesh.population(self.config.member)?;
+        evolution.current_psnr = evolution.psnr(mesh)?;
+        evolution.best = Some((evolution.current_psnr, mesh.clone()));
+        evolution.previous = Some(previous);
+        evolution.generation = mesh.run.as_ref().map_or(0, |run| run.generation) + 1;
+        Ok(evolution)
+    }
+    /// Continue the run saved in `checkpoint` on the image it was started on.
+    ///
+    /// The run continues with the configuration of the checkpoint, the
+    /// options of this builder are ignored except for
+    /// [`Triangulator::interrupt`]. The time limit counts from the call to
+    /// `resume`.
+    pub fn resume(&self, image: &RgbImage, checkpoint: Checkpoint) -> Result<Evolution> {
+        checkpoint.config.validate()?;
+        if (checkpoint.mesh.width, checkpoint.mesh.height) != image.dimensions() {
+            return Err(Error::checkpoint(format!(
+                "checkpoint was made for a {}x{} image, not {}x{}",
+                checkpoint.mesh.width,
+                checkpoint.mesh.height,
+                image.width(),
+                image.height()
+            )));
+        }
+        let previous = checkpoint.mesh.population(checkpoint.config.member)?;
+        let mut evolution =
+            self.evolution(image, checkpoint.config, checkpoint.seed, checkpoint.rng);
+        evolution.current_psnr = evolution.psnr(&checkpoint.mesh)?;
+        evolution.previous = Some(previous);
+        evolution.generation = checkpoint.generation;
+        evolution.best = checkpoint.best;
+        evolution.best_fitness = checkpoint.best_fitness;
+        evolution.stale = checkpoint.stale;
+        Ok(evolution)
+    }
+    fn evolution(&self, image: &RgbImage, config: Config, seed: u32, rng: ChaCha8Rng) -> Evolution {
+        let n_points = config.run.segments.pow(2) as f32;
+        Evolution {
+            img: Img::new(image.clone(), n_points),
+            cache: Cache::new(),
+            config,
+            rng,
+            seed,
+            previous: None,
+            current_psnr: f64::NEG_INFINITY,
+            generation: 0,
+            started: Instant::now(),
+            interrupt: self.interrupt.clone(),
+            best: None,
+            best_fitness: None,
+            stale: 0,
+            stop: None,
+            observers: vec![],
+        }
+    }
+    /// Compare `mesh` against `image`.
+    ///
+    /// Face fitness is only computed when the mesh was evolved on an image of
+    /// the same dimensions.
+    pub fn score(&self, mesh: &Mesh, image: &RgbImage) -> Result<Score> {
+        let rendered = mesh.render(image.dimensions())?;
+        let (mse, psnr) = score::difference(&rendered, image);
+        let img = Img::new(image.clone(), mesh.points.len() as f32);
+        let fitness = if mesh.dimensions() == img.dimensions() {
+            let mut cache = Cache::new();
+            let population = mesh.population(self.config.member)?;
+            let gen = Generation::new(&population, &img, &mut cache, &self.config)?;
+            Some(gen.current().fitness())
+        } else {
+            None
+        };
+        Ok(Score { mse, psnr, fitness })
+    }
+}
+
+/// Iterator over the generations of a run, see [`Triangulator::evolve`].
+pub struct Evolution {
+    img: Img,
+    cache: Cache,
+    config: Config,
+    rng: ChaCha8Rng,
+    seed: u32,
+    // last accepted generation, the next one is evolved from it
+    previous: Option<Population>,
+    current_psnr: f64,
+    generation: u32,
+    started: Instant,
+    interrupt: Option<Arc<AtomicBool>>,
+    // hall of fame, the best mesh so far and its PSNR
+    best: Option<(f64, Mesh)>,
+    // best average fitness so far, for stop.plateau
+    best_fitness: Option<f32>,
+    // generations since the best average fitness was reached
+    stale: u32,
+    stop: Option<Stop>,
+    observers: Vec<Box<dyn Observer>>,
+}
+
+impl Evolution {
+    /// Number of the generation the next call to `next` evolves.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+    /// Seed of the run, pass it to [`Triangulator::seed`] to repeat it.
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
+    /// Report the progress of the run to `observer`.
+    pub fn observe<O: Observer + 'static>(&mut self, observer: O) {
+        self.observers.push(Box::new(observer));
+    }
+    /// Why the run stopped, `None` while it is running or after an error.
+    pub fn stopped(&self) -> Option<Stop> {
+        self.stop
+    }
+    /// Snapshot of the run to continue it later with
+    /// [`Triangulator::resume`], `None` before the first generation.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        let previous = self.previous.as_ref()?;
+        Some(Checkpoint {
+            config: self.config.clone(),
+            seed: self.seed,
+            generation: self.generation,
+            mesh: Mesh::new(previous, self.img.dimensions()),
+            rng: self.rng.clone(),
+            best: self.best.clone(),
+            best_fitness: self.best_fitness,
+            stale: self.stale,
+        })
+    }
+    /// Generation with the best PSNR against the image so far, whatever
+    /// the acceptance rule.
+    pub fn best(&self) -> Option<&Mesh> {
+        self.best.as_ref().map(|(_, mesh)| mesh)
+    }
+    // Criterion met before evolving the next generation
+    fn stop_before(&mut self) -> Option<Stop> {
+        if cancelled(&self.interrupt, &mut self.observers) {
+            return Some(Stop::Interrupted);
+        }
+        if let Some(limit) = self.config.stop.time_limit {
+            if self.started.elapsed().as_secs_f64() >= limit {
+                return Some(Stop::TimeLimit);
+            }
+        }
+        None
+    }
+    // Criterion met by the generation just evolved
+    fn stop_after(&self, fitness: f32, psnr: f64) -> Option<Stop> {
+        let stop = &self.config.stop;
+        if stop.target_fitness.is_some_and(|target| fitness >= target) {
+            return Some(Stop::TargetFitness);
+        }
+        if stop.target_psnr.is_some_and(|target| psnr >= target) {
+            return Some(Stop::TargetPsnr);
+        }
+        if stop.plateau.is_some_and(|n| self.stale >= n) {
+            return Some(Stop::Plateau);
+        }
+        if self.generation > self.config.run.generations {
+            return Some(Stop::Generations);
+        }
+        None
+    }
+    // Metadata of the meshes yielded for `generation`
+    fn run_info(&self, generation: u32) -> RunInfo {
+        let mut config = self.config.clone();
+        config.run.seed = Some(self.seed);
+        RunInfo {
+            config,
+            seed: self.seed,
+            generation,
+        }
+    }
+    // Whole-image metric generations are judged by
+    fn psnr(&self, mesh: &Mesh) -> Result<f64> {
+        let rendered = mesh.render(self.img.image().dimensions())?;
+        let (_, psnr) = score::difference(&rendered, self.img.image());
+        Ok(psnr)
+    }
+    // Whether a generation with `psnr` replaces the current one
+    fn accepts(&self, psnr: f64) -> bool {
+        if self.previous.is_none() {
+            return true;
+        }
+        let elitism = &self.config.elitism;
+        match elitism.acceptance {
+            Acceptance::Always => true,
+            Acceptance::Improve => psnr > self.current_psnr,
+            Acceptance::Tolerance => {
+                let best = self.best.as_ref().map_or(f64::NEG_INFINITY, |(b, _)| *b);
+                psnr >= best - elitism.tolerance
+            }
+        }
+    }
+    // None when the generation was cancelled between mutation rounds
+    fn step(&mut self) -> Result<Option<(Population, f32)>> {
+        match &self.previous {
+            // Calculate fitness and create 0th generation
+            None => {
+                let points = initial_points(self.img.dimensions(), self.config.run.segments);
+                let mut gen = Generation::from(points, &self.img, &mut self.cache, &self.config)?;
+                let population = gen.get_best_population()?;
+                Ok(Some((population, gen.average_fitness())))
+            }
+            Some(previous) => {
+                // - Create generation from previous generation (new base members)
+                let mut gen = Generation::new(previous, &self.img, &mut self.cache, &self.config)?;
+                // - Mutate each base member equal number of times
+                // - Calculate fitness of each new member
+                // - If fitness is higher than base member, its marked as beneficial
+                // - Base members are copied again, mutating them with all beneficial mutations
+                // - Calculate fitness of new mutated base members
+                let (interrupt, observers) = (&self.interrupt, &mut self.observers);
+                // a cancelled generation must not use up random numbers
+                let rng = self.rng.clone();
+                let completed = gen.mutate(
+                    self.config.run.mutations_per_generation,
+                    &mut self.rng,
+                    &mut || cancelled(interrupt, observers),
+                )?;
+                if !completed {
+                    self.rng = rng;
+                    return Ok(None);
+                }
+
+                // - Sort all members by fitness
+                let population = gen.get_best_population()?;
+                Ok(Some((population, gen.average_fitness())))
+            }
+        }
+    }
+    fn finish(&mut self, stop: Stop) {
+        self.stop = Some(stop);
+        for observer in &mut self.observers {
+            observer.stopped(stop);
+        }
+    }
+    // Nothing to continue from after an error
+    fn fail(&mut self) {
+        self.previous = None;
+        self.generation = self.config.run.generations + 1;
+    }
+}
+
+impl Iterator for Evolution {
+    type Item = Result<Mesh>;
+
+    fn next(&mut self) -> Option<Result<Mesh>> {
+        if self.stop.is_some() || self.generation > self.config.run.generations {
+            return None;
+        }
+        if let Some(stop) = self.stop_before() {
+            self.finish(stop);
+            return None;
+        }
+        let started = Instant::now();
+        for observer in &mut self.observers {
+            observer.generation_started(self.generation);
+        }
+        let (population, fitness) = match self.step() {
+            Ok(Some(step)) => step,
+            Ok(None) => {
+                self.finish(Stop::Interrupted);
+                return None;
+            }
+            Err(e) => {
+                self.fail();
+                return Some(Err(e));
+            }
+        };
+        let mut mesh = Mesh::new(&population, self.img.dimensions());
+        mesh.run = Some(self.run_info(self.generation));
+        let psnr = match self.psnr(&mesh) {
+            Ok(psnr) => psnr,
+            Err(e) => {
+                self.fail();
+                return Some(Err(e));
+            }
+        };
+        let accepted = self.accepts(psnr);
+        let face_fitness = population.faces.iter().map(|face| face.fitness);
+        let stats = Stats {
+            generation: self.generation,
+            average_fitness: fitness,
+            total_fitness: population.fitness(),
+            min_face_fitness: face_fitness.clone().fold(f32::INFINITY, f32::min),
+            max_face_fitness: face_fitness.fold(f32::NEG_INFINITY, f32::max),
+            psnr,
+            accepted,
+            points: population.points.len(),
+            faces: population.faces.len(),
+            elapsed: started.elapsed(),
+        };
+        self.generation += 1;
+
+        match self.best_fitness {
+            Some(best) if best >= fitness => self.stale += 1,
+            _ => {
+                self.best_fitness = Some(fitness);
+                self.stale = 0;
+            }
+        }
+        if self.best.as_ref().is_none_or(|(best, _)| psnr > *best) {
+            self.best = Some((psnr, mesh.clone()));
+            for observer in &mut self.observers {
+                observer.best_changed(stats.generation, &mesh);
+            }
+        }
+        // a rejected generation is replaced by the one it was evolved from
+        let mesh = if accepted {
+            self.previous = Some(population);
+            self.current_psnr = psnr;
+            mesh
+        } else {
+            let previous = self.previous.as_ref().expect("only later generations are rejected");
+            let mut mesh = Mesh::new(previous, self.img.dimensions());
+            mesh.run = Some(self.run_info(stats.generation));
+            // the same points as the mesh yielded before
+            mesh.origins = (0..mesh.points.len()).collect();
+            mesh
+        };
+        for observer in &mut self.observers {
+            observer.generation_finished(&stats, &mesh);
+        }
+        if let Some(stop) = self.stop_after(fitness, psnr) {
+            self.finish(stop);
+        }
+        Some(Ok(mesh))
+    }
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self {
+            Stop::Generations => "every generation evolved",
+            Stop::TimeLimit => "time limit reached",
+            Stop::Plateau => "average fitness stopped improving",
+            Stop::TargetFitness => "target fitness reached",
+            Stop::TargetPsnr => "target psnr reached",
+            Stop::Interrupted => "interrupted",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+fn initial_points((w, h): (f32, f32), segments: u32) -> Vec<(f32, f32)> {
+    // Create random points across image
+    let mut points = vec![];
+    for i in 0..segments {
+        for j in 0..segments {
+            points.push((
+                i as f32 * (w / (segments - 1) as f32),
+                j as f32 * (h / (segments - 1) as f32),
+            ));
+        }
+    }
+    points
+}
+
+fn cancelled(interrupt: &Option<Arc<AtomicBool>>, observers: &mut [Box<dyn Observer>]) -> bool {
+    if interrupt
+        .as_ref()
+        .is_some_and(|flag| flag.load(Ordering::SeqCst))
+    {
+        return true;
+    }
+    observers.iter_mut().any(|observer| observer.cancelled())
+}
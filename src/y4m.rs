@@ -0,0 +1,87 @@
+//! Raw YUV4MPEG2 video, as read by ffmpeg and most encoders.
+//!
+//! Frames are 4:2:0 full range BT.601 (`C420jpeg`) and written as soon as
+//! they are pushed, so the stream can be piped into an encoder.
+
+use image::RgbImage;
+use std::io::{self, Write};
+
+/// A Y4M stream of frames of the same size.
+pub struct Y4m<W: Write> {
+    out: W,
+    size: (u32, u32),
+}
+
+impl<W: Write> Y4m<W> {
+    /// Write the stream header for frames of `size` at `fps` frames per
+    /// second.
+    pub fn new(mut out: W, size: (u32, u32), fps: u32) -> io::Result<Y4m<W>> {
+        if size.0 == 0 || size.1 == 0 || fps == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "can not stream frames of {}x{} at {} fps",
+                    size.0, size.1, fps
+                ),
+            ));
+        }
+        writeln!(
+            out,
+            "YUV4MPEG2 W{} H{} F{}:1 Ip A1:1 C420jpeg XYSCSS=420JPEG",
+            size.0, size.1, fps
+        )?;
+        Ok(Y4m { out, size })
+    }
+    /// Write a frame, it must have the size the stream was started with.
+    pub fn push(&mut self, frame: &RgbImage) -> io::Result<()> {
+        let (width, height) = self.size;
+        if frame.dimensions() != self.size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "frame of {}x{} in a stream of {}x{}",
+                    frame.width(),
+                    frame.height(),
+                    width,
+                    height
+                ),
+            ));
+        }
+        let mut data = Vec::with_capacity((width * height * 3 / 2 + 2) as usize);
+        for pixel in frame.pixels() {
+            let [r, g, b] = pixel.0.map(|c| c as f32);
+            data.push(channel(0.299 * r + 0.587 * g + 0.114 * b));
+        }
+        // chroma of every 2x2 block, averaged over the pixels it has at the edges
+        let (cw, ch) = (width.div_ceil(2), height.div_ceil(2));
+        let mut u = Vec::with_capacity((cw * ch) as usize);
+        let mut v = Vec::with_capacity((cw * ch) as usize);
+        for y in 0..ch {
+            for x in 0..cw {
+                let mut sum = [0.0; 3];
+                let mut n = 0.0;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let (px, py) = (x * 2 + dx, y * 2 + dy);
+                    if px < width && py < height {
+                        for (total, c) in sum.iter_mut().zip(frame.get_pixel(px, py).0) {
+                            *total += c as f32;
+                        }
+                        n += 1.0;
+                    }
+                }
+                let [r, g, b] = sum.map(|total| total / n);
+                u.push(channel(128.0 - 0.168_736 * r - 0.331_264 * g + 0.5 * b));
+                v.push(channel(128.0 + 0.5 * r - 0.418_688 * g - 0.081_312 * b));
+            }
+        }
+        data.extend_from_slice(&u);
+        data.extend_from_slice(&v);
+        self.out.write_all(b"FRAME\n")?;
+        self.out.write_all(&data)?;
+        self.out.flush()
+    }
+}
+
+fn channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
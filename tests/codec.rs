@@ -0,0 +1,72 @@
+mod common;
+
+use common::mesh;
+use std::collections::HashSet;
+use traingle::codec::{self, Options};
+
+#[test]
+fn round_trip() {
+    let mesh = mesh();
+    let data = codec::encode(&mesh, &Options::default());
+    let decoded = codec::decode(&data).unwrap();
+    assert_eq!((decoded.width, decoded.height), (mesh.width, mesh.height));
+    // evolved points are apart by more than the precision
+    assert_eq!(decoded.points.len(), mesh.points.len());
+    assert_eq!(decoded.triangles.len(), mesh.triangles.len());
+    assert_eq!(decoded.colors.len(), decoded.triangles.len());
+    for (decoded, point) in decoded.points.iter().zip(sorted(&mesh.points)) {
+        assert!((decoded.0 - point.0).abs() <= 0.125 && (decoded.1 - point.1).abs() <= 0.125);
+    }
+
+    // a decoded mesh encodes to the same bytes
+    assert_eq!(codec::encode(&decoded, &Options::default()), data);
+    let again = codec::decode(&data).unwrap();
+    assert_eq!(again.points, decoded.points);
+    assert_eq!(again.triangles, decoded.triangles);
+    assert_eq!(again.colors, decoded.colors);
+}
+
+#[test]
+fn palette() {
+    let options = Options {
+        palette: Some(4),
+        ..Options::default()
+    };
+    let decoded = codec::decode(&codec::encode(&mesh(), &options)).unwrap();
+    let colors: HashSet<[u8; 3]> = decoded.colors.iter().copied().collect();
+    assert!(colors.len() <= 4);
+    assert_eq!(decoded.colors.len(), decoded.triangles.len());
+}
+
+#[test]
+fn truncated() {
+    let data = codec::encode(&mesh(), &Options::default());
+    for length in 0..data.len() {
+        assert!(
+            codec::decode(&data[..length]).is_err(),
+            "decoded {} of {} bytes",
+            length,
+            data.len()
+        );
+    }
+}
+
+#[test]
+fn corrupted() {
+    let data = codec::encode(&mesh(), &Options::default());
+    assert!(codec::decode(b"GIF89a").is_err());
+    // any byte may change, decoding fails or gives a mesh but never panics
+    for i in 0..data.len() {
+        for flip in [0x01, 0x80, 0xff] {
+            let mut corrupted = data.clone();
+            corrupted[i] ^= flip;
+            let _ = codec::decode(&corrupted);
+        }
+    }
+}
+
+fn sorted(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points
+}
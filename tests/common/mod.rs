@@ -0,0 +1,34 @@
+// Fixtures shared by the integration tests
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+use traingle::image::{Rgb, RgbImage};
+use traingle::{Mesh, Triangulator};
+
+// A gradient small enough to evolve quickly
+pub fn image() -> RgbImage {
+    RgbImage::from_fn(64, 48, |x, y| {
+        Rgb([(x * 4) as u8, (y * 5) as u8, ((x + y) * 2) as u8])
+    })
+}
+
+// Builder of the runs the tests evolve, seeded so they are repeatable
+pub fn triangulator() -> Triangulator {
+    Triangulator::new()
+        .segments(8)
+        .generations(2)
+        .mutations(2)
+        .seed(1)
+}
+
+pub fn mesh() -> Mesh {
+    triangulator().run(&image()).unwrap()
+}
+
+// Empty directory for the files of the test `name`
+pub fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("traingle-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::mesh;
+use std::collections::{HashMap, HashSet};
+use traingle::export::dxf::{self, Entities, Options};
+use traingle::Mesh;
+
+fn write(mesh: &Mesh, options: &Options) -> String {
+    let mut out = vec![];
+    dxf::write(mesh, options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// Group codes and values, panics unless every code is a number
+fn pairs(dxf: &str) -> Vec<(u16, &str)> {
+    let lines: Vec<&str> = dxf.lines().collect();
+    assert_eq!(lines.len() % 2, 0);
+    lines
+        .chunks(2)
+        .map(|pair| (pair[0].trim().parse().unwrap(), pair[1]))
+        .collect()
+}
+
+// Panics unless every handle is unique and below the handle seed, and every
+// owner, layer and line type referred to is in the drawing
+fn check_references(dxf: &str) {
+    let pairs = pairs(dxf);
+    let seed = pairs
+        .windows(2)
+        .find(|w| w[0] == (9, "$HANDSEED"))
+        .map(|w| u64::from_str_radix(w[1].1, 16).unwrap())
+        .unwrap();
+    // after the header, where the handle seed is in a 5 as well
+    let header = pairs
+        .iter()
+        .position(|pair| *pair == (0, "ENDSEC"))
+        .unwrap();
+    let pairs = &pairs[header..];
+    let mut handles = HashSet::new();
+    for (code, value) in pairs {
+        if *code == 5 || *code == 105 {
+            assert!(handles.insert(u64::from_str_radix(value, 16).unwrap()));
+        }
+    }
+    assert!(handles.iter().all(|handle| *handle < seed));
+
+    // names of the records of every table
+    let mut names: HashMap<&str, HashSet<String>> = HashMap::new();
+    let mut kind = "";
+    for (code, value) in pairs {
+        match code {
+            0 => kind = value,
+            2 if kind != "SECTION" && kind != "TABLE" => {
+                names.entry(kind).or_default().insert(value.to_lowercase());
+            }
+            _ => {}
+        }
+    }
+    for (code, value) in pairs {
+        match code {
+            330 if *value != "0" => {
+                assert!(handles.contains(&u64::from_str_radix(value, 16).unwrap()))
+            }
+            6 => assert!(names["LTYPE"].contains(&value.to_lowercase()), "{}", value),
+            8 => assert!(names["LAYER"].contains(&value.to_lowercase()), "{}", value),
+            _ => {}
+        }
+    }
+}
+
+#[test]
+fn lines() {
+    let mesh = mesh();
+    let dxf = write(&mesh, &Options::default());
+    check_references(&dxf);
+
+    let drawing = ::dxf::Drawing::load(&mut dxf.as_bytes()).unwrap();
+    assert_eq!(drawing.header.version, ::dxf::enums::AcadVersion::R2000);
+    assert!(drawing
+        .line_types()
+        .any(|t| t.name.eq_ignore_ascii_case("continuous")));
+    let colors: HashSet<[u8; 3]> = mesh.colors.iter().copied().collect();
+    assert_eq!(drawing.layers().count(), colors.len() + 1);
+    let blocks: Vec<&str> = drawing.blocks().map(|b| b.name.as_str()).collect();
+    assert_eq!(blocks, ["*Model_Space", "*Paper_Space"]);
+
+    let edges: HashSet<(usize, usize)> = mesh
+        .triangles
+        .iter()
+        .flat_map(|[a, b, c]| vec![(*a, *b), (*b, *c), (*c, *a)])
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect();
+    let lines = drawing
+        .entities()
+        .filter(|e| matches!(e.specific, ::dxf::entities::EntityType::Line(_)))
+        .count();
+    assert_eq!(lines, edges.len());
+    assert_eq!(drawing.entities().count(), lines);
+}
+
+#[test]
+fn polylines() {
+    let mesh = mesh();
+    let options = Options {
+        entities: Entities::Polylines,
+        colors: Some(3),
+        scale: 0.5,
+    };
+    let dxf = write(&mesh, &options);
+    check_references(&dxf);
+
+    let drawing = ::dxf::Drawing::load(&mut dxf.as_bytes()).unwrap();
+    assert!(drawing.layers().count() <= 4);
+    let mut polylines = 0;
+    for entity in drawing.entities() {
+        match &entity.specific {
+            ::dxf::entities::EntityType::LwPolyline(polyline) => {
+                assert!(polyline.is_closed());
+                assert_eq!(polyline.vertices.len(), 3);
+                for vertex in &polyline.vertices {
+                    assert!(vertex.x >= 0.0 && vertex.x <= mesh.width as f64 * 0.5 + 0.01);
+                    assert!(vertex.y >= 0.0 && vertex.y <= mesh.height as f64 * 0.5 + 0.01);
+                }
+                polylines += 1;
+            }
+            other => panic!("unexpected entity {:?}", other),
+        }
+    }
+    assert_eq!(polylines, mesh.triangles.len());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use common::image;
+use std::cell::RefCell;
+use std::rc::Rc;
+use traingle::config::Acceptance;
+use traingle::{Config, Mesh, Observer, Stats, Triangulator};
+
+struct Recorder(Rc<RefCell<Vec<Stats>>>);
+
+impl Observer for Recorder {
+    fn generation_finished(&mut self, stats: &Stats, _mesh: &Mesh) {
+        self.0.borrow_mut().push(*stats);
+    }
+}
+
+// Stats and meshes of every generation, and the best mesh of a run
+fn run(acceptance: Acceptance) -> (Vec<Stats>, Vec<Mesh>, Mesh) {
+    let mut config = Config::default();
+    config.elitism.acceptance = acceptance;
+    config.elitism.tolerance = 1.0;
+    let mut evolution = Triangulator::new()
+        .config(config)
+        .segments(8)
+        .generations(8)
+        .mutations(2)
+        .seed(1)
+        .evolve(&image())
+        .unwrap();
+    let stats = Rc::new(RefCell::new(vec![]));
+    evolution.observe(Recorder(stats.clone()));
+    let meshes = evolution.by_ref().map(Result::unwrap).collect();
+    let best = evolution.best().unwrap().clone();
+    let stats = stats.borrow().clone();
+    (stats, meshes, best)
+}
+
+#[test]
+fn improve() {
+    let (stats, meshes, _) = run(Acceptance::Improve);
+    let mut current = stats[0].psnr;
+    assert!(stats[0].accepted);
+    for (i, s) in stats.iter().enumerate().skip(1) {
+        assert_eq!(s.accepted, s.psnr > current, "generation {}", s.generation);
+        if s.accepted {
+            current = s.psnr;
+        } else {
+            // the run goes on from the generation before
+            assert_eq!(meshes[i].points, meshes[i - 1].points);
+            assert_eq!(meshes[i].run.as_ref().unwrap().generation, s.generation);
+        }
+    }
+    assert!(stats.iter().any(|s| !s.accepted));
+}
+
+#[test]
+fn always() {
+    let (stats, _, _) = run(Acceptance::Always);
+    assert!(stats.iter().all(|s| s.accepted));
+    assert!(stats.windows(2).any(|w| w[1].psnr < w[0].psnr));
+}
+
+#[test]
+fn tolerance() {
+    let (stats, _, _) = run(Acceptance::Tolerance);
+    let mut best = stats[0].psnr;
+    for s in &stats[1..] {
+        assert_eq!(
+            s.accepted,
+            s.psnr >= best - 1.0,
+            "generation {}",
+            s.generation
+        );
+        best = best.max(s.psnr);
+    }
+    // worse generations are accepted, much worse ones are not
+    assert!(stats[1..]
+        .iter()
+        .any(|s| s.accepted && s.psnr < stats[0].psnr));
+    assert!(stats.iter().any(|s| !s.accepted));
+}
+
+#[test]
+fn best_has_the_highest_psnr() {
+    for acceptance in [Acceptance::Always, Acceptance::Improve] {
+        let (stats, _, best) = run(acceptance);
+        let highest = stats
+            .iter()
+            .max_by(|a, b| a.psnr.total_cmp(&b.psnr))
+            .unwrap();
+        assert_eq!(best.run.as_ref().unwrap().generation, highest.generation);
+        let score = Triangulator::new().score(&best, &image()).unwrap();
+        assert_eq!(score.psnr, highest.psnr);
+    }
+}
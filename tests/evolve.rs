@@ -0,0 +1,80 @@
+mod common;
+
+use common::{image, triangulator};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use traingle::{Checkpoint, Config, Error, Mesh, Result, Stop, Triangulator};
+
+// A generation as the bytes it's saved as
+fn saved(mesh: Result<Mesh>) -> Vec<u8> {
+    serde_json::to_vec(&mesh.unwrap()).unwrap()
+}
+
+#[test]
+fn interrupted_before_the_initial_generation() {
+    let result = triangulator()
+        .interrupt(Arc::new(AtomicBool::new(true)))
+        .run(&image());
+    assert!(matches!(result, Err(Error::Stopped(Stop::Interrupted))));
+}
+
+#[test]
+fn same_seed_same_meshes() {
+    let run = || -> Vec<Vec<u8>> {
+        triangulator()
+            .generations(6)
+            .evolve(&image())
+            .unwrap()
+            .map(saved)
+            .collect()
+    };
+    let first = run();
+    assert_eq!(first.len(), 7);
+    assert_eq!(first, run());
+}
+
+#[test]
+fn resume_continues_where_the_run_left_off() {
+    let image = image();
+    let uninterrupted: Vec<Vec<u8>> = triangulator()
+        .generations(6)
+        .evolve(&image)
+        .unwrap()
+        .map(saved)
+        .collect();
+
+    let mut evolution = triangulator().generations(6).evolve(&image).unwrap();
+    let mut resumed: Vec<Vec<u8>> = evolution.by_ref().take(3).map(saved).collect();
+    // through JSON, as the checkpoint file
+    let file = serde_json::to_string(&evolution.checkpoint().unwrap()).unwrap();
+    let checkpoint: Checkpoint = serde_json::from_str(&file).unwrap();
+    resumed.extend(
+        Triangulator::new()
+            .resume(&image, checkpoint)
+            .unwrap()
+            .map(saved),
+    );
+    assert_eq!(resumed.len(), 7);
+    assert_eq!(resumed, uninterrupted);
+}
+
+#[test]
+fn face_size_threshold() {
+    let run = |threshold: f32| {
+        let mut config = Config::default();
+        config.generation.face_size_threshold = threshold;
+        Triangulator::new()
+            .config(config)
+            .segments(8)
+            .generations(2)
+            .mutations(2)
+            .seed(1)
+            .run(&image())
+    };
+    let every_face = serde_json::to_vec(&run(0.0).unwrap()).unwrap();
+    assert_eq!(every_face, serde_json::to_vec(&common::mesh()).unwrap());
+    let large_faces = serde_json::to_vec(&run(10.0).unwrap()).unwrap();
+    assert_ne!(large_faces, every_face);
+    // no face is as large as the image
+    assert!(matches!(run(64.0 * 48.0), Err(Error::Geometry(_))));
+}
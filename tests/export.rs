@@ -0,0 +1,63 @@
+mod common;
+
+use common::{dir, mesh};
+use std::path::Path;
+use traingle::export::{dxf, gltf, html, pdf, plotter, stl};
+use traingle::{Error, Result};
+
+// Panics unless `save` rejects its options and leaves the file at `path` as
+// it was
+fn rejected(path: &Path, save: impl FnOnce(&Path) -> Result<()>) {
+    std::fs::write(path, "earlier export").unwrap();
+    match save(path) {
+        Err(Error::Io { source, .. }) => {
+            assert_eq!(source.kind(), std::io::ErrorKind::InvalidInput)
+        }
+        other => panic!("saved with invalid options: {:?}", other),
+    }
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "earlier export");
+}
+
+#[test]
+fn invalid_options_keep_the_existing_file() {
+    let mesh = mesh();
+    let dir = dir("export-invalid-options");
+    rejected(&dir.join("mesh.pdf"), |path| {
+        let options = pdf::Options {
+            margin: -1.0,
+            ..pdf::Options::default()
+        };
+        pdf::save(&mesh, &options, path)
+    });
+    rejected(&dir.join("mesh.glb"), |path| {
+        let options = gltf::Options {
+            width: 0.0,
+            ..gltf::Options::default()
+        };
+        gltf::save(&mesh, &options, path)
+    });
+    rejected(&dir.join("mesh.stl"), |path| {
+        let options = stl::Options {
+            base: 0.0,
+            ..stl::Options::default()
+        };
+        stl::save(&mesh, &options, path)
+    });
+    rejected(&dir.join("plot.svg"), |path| {
+        let options = plotter::Options {
+            pen: -0.5,
+            ..plotter::Options::default()
+        };
+        plotter::save(&mesh, &options, path)
+    });
+    rejected(&dir.join("mesh.dxf"), |path| {
+        let options = dxf::Options {
+            scale: f32::NAN,
+            ..dxf::Options::default()
+        };
+        dxf::save(&mesh, &options, path)
+    });
+    rejected(&dir.join("run.html"), |path| {
+        html::save(&[], &html::Options::default(), path)
+    });
+}
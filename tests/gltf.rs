@@ -0,0 +1,54 @@
+mod common;
+
+use common::mesh;
+use traingle::export;
+
+// Triangles of the single primitive of a GLB, which has no indices
+fn triangles(glb: &[u8]) -> usize {
+    let (document, buffers, _) = ::gltf::import_slice(glb).unwrap();
+    let meshes: Vec<_> = document.meshes().collect();
+    assert_eq!(meshes.len(), 1);
+    let primitives: Vec<_> = meshes[0].primitives().collect();
+    assert_eq!(primitives.len(), 1);
+    let primitive = &primitives[0];
+    assert_eq!(primitive.mode(), ::gltf::mesh::Mode::Triangles);
+    assert!(primitive.indices().is_none());
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions: Vec<[f32; 3]> = reader.read_positions().unwrap().collect();
+    let colors: Vec<[f32; 3]> = reader.read_colors(0).unwrap().into_rgb_f32().collect();
+    assert_eq!(positions.len(), colors.len());
+    assert_eq!(positions.len() % 3, 0);
+    positions.len() / 3
+}
+
+#[test]
+fn glb_round_trip() {
+    let mesh = mesh();
+    let mut glb = vec![];
+    export::gltf::write(&mesh, &export::gltf::Options::default(), &mut glb).unwrap();
+    // one triangle per face of the evolved population
+    assert_eq!(triangles(&glb), mesh.triangles.len());
+}
+
+#[test]
+fn extruded_glb_round_trip() {
+    let mesh = mesh();
+    let options = export::gltf::Options {
+        extrude: Some(0.1),
+        ..export::gltf::Options::default()
+    };
+    let mut glb = vec![];
+    export::gltf::write(&mesh, &options, &mut glb).unwrap();
+    // front, back and two triangles per side on the outline
+    let triangles = triangles(&glb);
+    assert!(triangles > 2 * mesh.triangles.len());
+    assert_eq!((triangles - 2 * mesh.triangles.len()) % 2, 0);
+}
+
+#[test]
+fn gltf_json_round_trip() {
+    let mesh = mesh();
+    let mut json = vec![];
+    export::gltf::write_json(&mesh, &export::gltf::Options::default(), &mut json).unwrap();
+    assert_eq!(triangles(&json), mesh.triangles.len());
+}
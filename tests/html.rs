@@ -0,0 +1,74 @@
+mod common;
+
+use common::mesh;
+use traingle::export::html;
+use traingle::Mesh;
+
+fn write(meshes: &[Mesh], title: &str) -> String {
+    let options = html::Options {
+        title: title.to_string(),
+    };
+    let mut out = vec![];
+    html::write(meshes, &options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// The meshes embedded in the page
+fn embedded(page: &str) -> Vec<Mesh> {
+    let open = "<script id=\"meshes\" type=\"application/json\">";
+    let start = page.find(open).unwrap() + open.len();
+    let json = &page[start..start + page[start..].find("</script>").unwrap()];
+    serde_json::from_str(json).unwrap()
+}
+
+fn generations(meshes: &[Mesh]) -> Vec<u32> {
+    meshes
+        .iter()
+        .map(|mesh| mesh.run.as_ref().unwrap().generation)
+        .collect()
+}
+
+#[test]
+fn ordered_by_generation() {
+    let mesh = mesh();
+    let meshes: Vec<Mesh> = [2, 0, 1]
+        .iter()
+        .map(|generation| {
+            let mut mesh = mesh.clone();
+            mesh.run.as_mut().unwrap().generation = *generation;
+            mesh
+        })
+        .collect();
+    let page = write(&meshes, "run");
+    assert!(page.contains("<title>run</title>"));
+    let embedded = embedded(&page);
+    assert_eq!(generations(&embedded), [0, 1, 2]);
+    assert_eq!(embedded[0].points, mesh.points);
+    assert_eq!(embedded[0].colors, mesh.colors);
+}
+
+#[test]
+fn placeholders_in_the_title() {
+    let mesh = mesh();
+    let page = write(&[mesh], "{meshes} & <{title}>");
+    assert!(page.contains("<title>{meshes} &amp; &lt;{title}&gt;</title>"));
+    assert_eq!(page.matches("<script id=\"meshes\"").count(), 1);
+    assert_eq!(embedded(&page).len(), 1);
+}
+
+#[test]
+fn markup_in_the_meshes() {
+    let mut mesh = mesh();
+    let filename = "</script><!--<script>";
+    mesh.run.as_mut().unwrap().config.output.filename = filename.to_string();
+    let page = write(&[mesh], "run");
+    // the page has only its own two script elements
+    assert_eq!(page.matches("<script").count(), 2);
+    assert_eq!(page.matches("</script>").count(), 2);
+    assert!(!page.contains("<!--"));
+    let embedded = embedded(&page);
+    assert_eq!(
+        embedded[0].run.as_ref().unwrap().config.output.filename,
+        filename
+    );
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use common::{dir, image, mesh, triangulator};
+use traingle::{Error, Mesh};
+
+#[test]
+fn save_and_load() {
+    let mesh = mesh();
+    let path = dir("save-and-load").join("mesh.json");
+    mesh.save(&path).unwrap();
+    let loaded = Mesh::load(&path).unwrap();
+    assert_eq!(loaded.version, mesh.version);
+    assert_eq!((loaded.width, loaded.height), (mesh.width, mesh.height));
+    assert_eq!(loaded.points, mesh.points);
+    assert_eq!(loaded.triangles, mesh.triangles);
+    assert_eq!(loaded.colors, mesh.colors);
+    assert_eq!(loaded.fitness, mesh.fitness);
+    assert_eq!(loaded.origins, mesh.origins);
+    let (run, loaded_run) = (mesh.run.unwrap(), loaded.run.as_ref().unwrap());
+    assert_eq!(loaded_run.seed, run.seed);
+    assert_eq!(loaded_run.generation, run.generation);
+
+    // a loaded mesh is a population to evolve on
+    let mut evolution = triangulator().evolve_from(&image(), &loaded).unwrap();
+    let next = evolution.next().unwrap().unwrap();
+    assert_eq!(next.run.unwrap().generation, run.generation + 1);
+    assert_eq!(next.points.len(), loaded.points.len());
+}
+
+#[test]
+fn duplicate_points() {
+    let mesh = mesh();
+    let size = (mesh.width, mesh.height);
+    // a hand-edited sliver between a point and its copy
+    let mut edited = mesh.clone();
+    let [a, b, _] = mesh.triangles[0];
+    edited.points.push(mesh.points[a]);
+    edited.triangles.push([a, edited.points.len() - 1, b]);
+    edited.colors.push(mesh.colors[0]);
+    edited.fitness.push(0.0);
+    edited.origins.push(a);
+    assert_eq!(edited.render(size).unwrap(), mesh.render(size).unwrap());
+    assert!(triangulator().evolve_from(&image(), &edited).is_ok());
+}
+
+// Message of the error loading `mesh` fails with, panics when it loads
+fn rejected(name: &str, mesh: &Mesh) -> String {
+    let path = dir(name).join("mesh.json");
+    mesh.save(&path).unwrap();
+    match Mesh::load(&path) {
+        Err(e @ Error::Mesh { .. }) => {
+            assert_eq!(e.path(), Some(path.as_path()));
+            e.to_string()
+        }
+        other => panic!("loaded a malformed mesh: {:?}", other.map(|_| ())),
+    }
+}
+
+#[test]
+fn missing_point() {
+    let mut mesh = mesh();
+    mesh.triangles[0][1] = mesh.points.len();
+    assert!(rejected("missing-point", &mesh).contains("missing point"));
+}
+
+#[test]
+fn colors_and_triangles_mismatch() {
+    let mut mesh = mesh();
+    mesh.colors.pop();
+    assert!(rejected("colors-mismatch", &mesh).contains("colors"));
+}
+
+#[test]
+fn fitness_and_triangles_mismatch() {
+    let mut mesh = mesh();
+    mesh.fitness.push(0.0);
+    assert!(rejected("fitness-mismatch", &mesh).contains("fitness"));
+}
+
+#[test]
+fn not_a_mesh() {
+    let path = dir("not-a-mesh").join("mesh.json");
+    std::fs::write(&path, r#"{"width": 4, "height": 4}"#).unwrap();
+    assert!(matches!(Mesh::load(&path), Err(Error::Mesh { .. })));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use common::{dir, image, mesh};
+use std::path::Path;
+use traingle::image::ImageFormat;
+use traingle::metadata::Metadata;
+
+fn metadata() -> Metadata {
+    Metadata::new(Path::new("photos/input.png"), "0123abcd", &mesh()).unwrap()
+}
+
+fn assert_same(read: &Metadata, written: &Metadata) {
+    assert_eq!(read.input, written.input);
+    assert_eq!(read.image_sha256, written.image_sha256);
+    assert_eq!(read.seed, written.seed);
+    assert_eq!(read.generation, written.generation);
+    assert_eq!(read.fitness, written.fitness);
+    assert_eq!(
+        serde_json::to_string(&read.config).unwrap(),
+        serde_json::to_string(&written.config).unwrap()
+    );
+}
+
+#[test]
+fn recorded_from_the_mesh() {
+    let mesh = mesh();
+    let metadata = metadata();
+    let run = mesh.run.as_ref().unwrap();
+    // only the file name of the input is kept
+    assert_eq!(metadata.input, "input.png");
+    assert_eq!(metadata.seed, 1);
+    assert_eq!(metadata.generation, run.generation);
+    assert_eq!(metadata.fitness, mesh.fitness.iter().sum::<f32>());
+
+    let mut unrecorded = mesh;
+    unrecorded.run = None;
+    assert!(Metadata::new(Path::new("input.png"), "", &unrecorded).is_none());
+}
+
+#[test]
+fn png_text_chunks() {
+    let path = dir("metadata-png").join("render.png");
+    let metadata = metadata();
+    let image = image();
+    metadata
+        .save_image(&image, &path, ImageFormat::Png)
+        .unwrap();
+    assert!(!Metadata::sidecar(&path).exists());
+    // the chunks leave the image as it was
+    let read = traingle::image::open(&path).unwrap().to_rgb8();
+    assert_eq!(read, image);
+    assert_same(&Metadata::load(&path).unwrap().unwrap(), &metadata);
+}
+
+#[test]
+fn sidecar() {
+    let path = dir("metadata-jpg").join("render.jpg");
+    let metadata = metadata();
+    metadata
+        .save_image(&image(), &path, ImageFormat::Jpeg)
+        .unwrap();
+    let sidecar = Metadata::sidecar(&path);
+    assert_eq!(sidecar, path.with_file_name("render.jpg.json"));
+    assert_same(&Metadata::load(&path).unwrap().unwrap(), &metadata);
+
+    // a render without a sidecar has no metadata
+    std::fs::remove_file(&sidecar).unwrap();
+    assert!(Metadata::load(&path).unwrap().is_none());
+}
+
+#[test]
+fn plain_png() {
+    let path = dir("metadata-plain").join("plain.png");
+    image().save(&path).unwrap();
+    assert!(Metadata::load(&path).unwrap().is_none());
+}
+
+#[test]
+fn hash_file() {
+    let path = dir("metadata-hash").join("empty");
+    std::fs::write(&path, "").unwrap();
+    assert_eq!(
+        Metadata::hash_file(&path).unwrap(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+}
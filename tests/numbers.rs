@@ -0,0 +1,83 @@
+mod common;
+
+use common::mesh;
+use traingle::export::numbers::{Kit, Options};
+
+fn luminance([r, g, b]: [u8; 3]) -> f32 {
+    0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    Some(&element[start..start + element[start..].find('"').unwrap()])
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[test]
+fn palette() {
+    let mesh = mesh();
+    for colors in 1..=16 {
+        let kit = Kit::new(&mesh, &Options { colors });
+        let palette = kit.palette();
+        assert!(!palette.is_empty() && palette.len() <= colors);
+        // light to dark, and every paint a different color
+        assert!(palette
+            .windows(2)
+            .all(|pair| luminance(pair[0]) >= luminance(pair[1])));
+        let mut distinct = palette.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(distinct.len(), palette.len());
+    }
+}
+
+#[test]
+fn legend() {
+    let kit = Kit::new(&mesh(), &Options { colors: 5 });
+    let mut out = vec![];
+    kit.write_legend(&mut out).unwrap();
+    let svg = String::from_utf8(out).unwrap();
+    // the first rect is the background
+    let swatches: Vec<&str> = svg
+        .split("<rect")
+        .skip(2)
+        .map(|rect| attribute(rect, "fill").unwrap())
+        .collect();
+    let expected: Vec<String> = kit.palette().iter().map(|c| hex(*c)).collect();
+    assert_eq!(swatches, expected);
+    for (n, color) in expected.iter().enumerate() {
+        assert!(svg.contains(&format!(">{}</text>", n + 1)));
+        assert!(svg.contains(&format!(">{}</text>", color)));
+    }
+}
+
+#[test]
+fn labels_inside_their_regions() {
+    let mesh = mesh();
+    let kit = Kit::new(&mesh, &Options { colors: 6 });
+    let mut out = vec![];
+    kit.write_sheet(&mut out).unwrap();
+    let svg = String::from_utf8(out).unwrap();
+    let labels: Vec<(f32, f32, usize)> = svg
+        .split("<text")
+        .skip(1)
+        .map(|text| {
+            let coordinate = |name| attribute(text, name).unwrap().parse::<f32>().unwrap();
+            let number = &text[text.find('>').unwrap() + 1..text.find('<').unwrap()];
+            (coordinate("x"), coordinate("y"), number.parse().unwrap())
+        })
+        .collect();
+    assert_eq!(labels.len(), kit.regions());
+    assert_eq!(svg.matches("<path").count(), kit.regions());
+
+    // the painting shows the paint of its number under every label
+    let scale = 8;
+    let painting = kit.render_painting(mesh.width * scale);
+    for (x, y, number) in labels {
+        let (x, y) = ((x * scale as f32) as u32, (y * scale as f32) as u32);
+        assert_eq!(painting.get_pixel(x, y).0, kit.palette()[number - 1]);
+    }
+}
@@ -0,0 +1,126 @@
+mod common;
+
+use common::{dir, mesh};
+use std::collections::HashMap;
+use traingle::export::obj;
+use traingle::Mesh;
+
+fn write(mesh: &Mesh, options: &obj::Options) -> String {
+    let mut out = vec![];
+    obj::write(mesh, options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn values(line: &str) -> Vec<f32> {
+    line.split(' ')
+        .skip(1)
+        .map(|v| v.parse().unwrap())
+        .collect()
+}
+
+// Twice the area of a triangle, positive when it winds counter-clockwise
+fn winding(a: &[f32], b: &[f32], c: &[f32]) -> f32 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+#[test]
+fn materials() {
+    let mesh = mesh();
+    let obj = write(&mesh, &obj::Options::default());
+    assert!(obj.contains("\nmtllib mesh.mtl\n"));
+    let vertices: Vec<Vec<f32>> = obj
+        .lines()
+        .filter(|l| l.starts_with("v "))
+        .map(values)
+        .collect();
+    assert_eq!(vertices.len(), mesh.points.len());
+    // y points up, so the image isn't mirrored
+    for (v, (x, y)) in vertices.iter().zip(&mesh.points) {
+        assert!((v[0] - x).abs() <= 0.005);
+        assert!((v[1] - (mesh.height as f32 - y)).abs() <= 0.005);
+        assert_eq!(v[2], 0.0);
+    }
+
+    let color_of: HashMap<[usize; 3], [u8; 3]> = mesh
+        .triangles
+        .iter()
+        .map(|t| {
+            let mut t = *t;
+            t.sort_unstable();
+            t
+        })
+        .zip(mesh.colors.iter().copied())
+        .collect();
+    let mut material = "";
+    let mut faces = 0;
+    for line in obj.lines() {
+        if let Some(name) = line.strip_prefix("usemtl ") {
+            material = name;
+        } else if let Some(indices) = line.strip_prefix("f ") {
+            let f: Vec<usize> = indices
+                .split(' ')
+                .map(|i| i.parse::<usize>().unwrap() - 1)
+                .collect();
+            let (a, b, c) = (&vertices[f[0]], &vertices[f[1]], &vertices[f[2]]);
+            assert!(winding(a, b, c) >= 0.0);
+            let mut key = [f[0], f[1], f[2]];
+            key.sort_unstable();
+            let [r, g, b] = color_of[&key];
+            assert_eq!(material, format!("color_{:02x}{:02x}{:02x}", r, g, b));
+            faces += 1;
+        }
+    }
+    assert_eq!(faces, mesh.triangles.len());
+}
+
+#[test]
+fn material_library() {
+    let mesh = mesh();
+    let path = dir("obj-materials").join("mesh.obj");
+    obj::save(&mesh, &obj::Options::default(), &path).unwrap();
+    let mtl = std::fs::read_to_string(path.with_extension("mtl")).unwrap();
+    let mut colors = mesh.colors.clone();
+    colors.sort_unstable();
+    colors.dedup();
+    assert_eq!(mtl.matches("newmtl ").count(), colors.len());
+    for [r, g, b] in colors {
+        let name = format!("newmtl color_{:02x}{:02x}{:02x}\n", r, g, b);
+        let kd = format!(
+            "Kd {:.4} {:.4} {:.4}",
+            r as f32 / 255.0,
+            g as f32 / 255.0,
+            b as f32 / 255.0
+        );
+        let at = mtl.find(&name).unwrap() + name.len();
+        assert!(mtl[at..].starts_with(&kd));
+    }
+}
+
+#[test]
+fn vertex_colors_and_height() {
+    let mesh = mesh();
+    let options = obj::Options {
+        colors: obj::Colors::Vertex,
+        height: Some(40.0),
+        ..obj::Options::default()
+    };
+    let obj = write(&mesh, &options);
+    assert!(!obj.contains("mtllib"));
+    let vertices: Vec<Vec<f32>> = obj
+        .lines()
+        .filter(|l| l.starts_with("v "))
+        .map(values)
+        .collect();
+    // every face has its own vertices so its color stays flat
+    assert_eq!(vertices.len(), mesh.triangles.len() * 3);
+    for (face, color) in vertices.chunks(3).zip(&mesh.colors) {
+        assert!(winding(&face[0], &face[1], &face[2]) >= 0.0);
+        for v in face {
+            assert!((0.0..=40.0).contains(&v[2]));
+            for (channel, value) in color.iter().zip(&v[3..]) {
+                assert!((*channel as f32 / 255.0 - value).abs() < 0.0001);
+            }
+        }
+    }
+    assert!(vertices.iter().any(|v| v[2] > 0.0));
+}
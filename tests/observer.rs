@@ -0,0 +1,122 @@
+mod common;
+
+use common::{image, triangulator};
+use std::cell::RefCell;
+use std::rc::Rc;
+use traingle::{Mesh, Observer, Stats, Stop};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Started(u32),
+    Finished(u32),
+    Best(u32),
+    Stopped(Stop),
+}
+
+// Records the events of a run, cancels it after `cancel_after` generations
+struct Recorder {
+    events: Rc<RefCell<Vec<Event>>>,
+    cancel_after: Option<usize>,
+}
+
+impl Recorder {
+    fn new(cancel_after: Option<usize>) -> (Recorder, Rc<RefCell<Vec<Event>>>) {
+        let events = Rc::new(RefCell::new(vec![]));
+        let recorder = Recorder {
+            events: events.clone(),
+            cancel_after,
+        };
+        (recorder, events)
+    }
+}
+
+impl Observer for Recorder {
+    fn generation_started(&mut self, generation: u32) {
+        self.events.borrow_mut().push(Event::Started(generation));
+    }
+    fn generation_finished(&mut self, stats: &Stats, _mesh: &Mesh) {
+        self.events
+            .borrow_mut()
+            .push(Event::Finished(stats.generation));
+    }
+    fn best_changed(&mut self, generation: u32, _mesh: &Mesh) {
+        self.events.borrow_mut().push(Event::Best(generation));
+    }
+    fn stopped(&mut self, stop: Stop) {
+        self.events.borrow_mut().push(Event::Stopped(stop));
+    }
+    fn cancelled(&mut self) -> bool {
+        let finished = self
+            .events
+            .borrow()
+            .iter()
+            .filter(|e| matches!(e, Event::Finished(_)))
+            .count();
+        self.cancel_after.is_some_and(|n| finished >= n)
+    }
+}
+
+#[test]
+fn event_order() {
+    let mut evolution = triangulator().generations(3).evolve(&image()).unwrap();
+    let (recorder, events) = Recorder::new(None);
+    evolution.observe(recorder);
+    assert_eq!(evolution.by_ref().count(), 4);
+    assert_eq!(evolution.stopped(), Some(Stop::Generations));
+
+    let events = events.borrow();
+    // the initial generation is always the best one so far
+    assert_eq!(
+        events[..3],
+        [Event::Started(0), Event::Best(0), Event::Finished(0)]
+    );
+    assert_eq!(events.last(), Some(&Event::Stopped(Stop::Generations)));
+    // a generation is started, maybe the new best and finished before the next
+    let mut generation = 0;
+    for event in &events[3..events.len() - 1] {
+        match event {
+            Event::Started(g) => {
+                generation += 1;
+                assert_eq!(*g, generation);
+            }
+            Event::Best(g) | Event::Finished(g) => assert_eq!(*g, generation),
+            Event::Stopped(_) => panic!("stopped before the last generation"),
+        }
+    }
+    assert_eq!(generation, 3);
+}
+
+#[test]
+fn cancelled_by_an_observer() {
+    let mut evolution = triangulator().generations(5).evolve(&image()).unwrap();
+    let (recorder, events) = Recorder::new(Some(2));
+    evolution.observe(recorder);
+    assert_eq!(evolution.by_ref().count(), 2);
+    assert_eq!(evolution.stopped(), Some(Stop::Interrupted));
+    // no generation is started once the run is cancelled
+    assert_eq!(evolution.next().map(|_| ()), None);
+    let events = events.borrow();
+    assert_eq!(events.last(), Some(&Event::Stopped(Stop::Interrupted)));
+    assert!(!events.contains(&Event::Started(2)));
+}
+
+#[test]
+fn stopped_by_a_target() {
+    let mut evolution = triangulator()
+        .generations(5)
+        .target_psnr(0.0)
+        .evolve(&image())
+        .unwrap();
+    assert_eq!(evolution.by_ref().count(), 1);
+    assert_eq!(evolution.stopped(), Some(Stop::TargetPsnr));
+
+    // stops the first time the average fitness does not improve
+    let mut evolution = triangulator()
+        .generations(50)
+        .plateau(1)
+        .evolve(&image())
+        .unwrap();
+    let evolved = evolution.by_ref().count();
+    assert!(evolved < 51);
+    assert_eq!(evolution.stopped(), Some(Stop::Plateau));
+}
@@ -0,0 +1,99 @@
+mod common;
+
+use common::mesh;
+use std::convert::TryInto;
+use traingle::export::pdf::{self, PageSize};
+
+const POINTS_PER_MM: f32 = 72.0 / 25.4;
+
+struct Page {
+    media: [f32; 4],
+    trim: Option<[f32; 4]>,
+    // x, y, width and height the faces are clipped to
+    clip: [f32; 4],
+}
+
+fn numbers<const N: usize>(text: &str) -> [f32; N] {
+    let values: Vec<f32> = text
+        .split_whitespace()
+        .map(|v| v.parse().unwrap())
+        .collect();
+    values.try_into().unwrap()
+}
+
+fn page(options: &pdf::Options) -> Page {
+    let mut out = vec![];
+    pdf::write(&mesh(), options, &mut out).unwrap();
+    let pdf = String::from_utf8(out).unwrap();
+    let array = |name: &str| {
+        let start = pdf.find(&format!("/{} [", name))? + name.len() + 3;
+        Some(numbers(
+            &pdf[start..start + pdf[start..].find(']').unwrap()],
+        ))
+    };
+    let stream = &pdf[pdf.find("stream\n").unwrap() + 7..];
+    let clip = stream.lines().next().unwrap();
+    Page {
+        media: array("MediaBox").unwrap(),
+        trim: array("TrimBox"),
+        clip: numbers(clip.strip_suffix(" re W n").unwrap()),
+    }
+}
+
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.02
+}
+
+#[test]
+fn margin() {
+    let page = page(&pdf::Options {
+        page: PageSize::A5,
+        margin: 10.0,
+        bleed: 0.0,
+        cover: true,
+        merge: false,
+    });
+    let (w, h) = (148.0 * POINTS_PER_MM, 210.0 * POINTS_PER_MM);
+    assert!(close(page.media[2], w) && close(page.media[3], h));
+    assert!(page.trim.is_none());
+    let m = 10.0 * POINTS_PER_MM;
+    let expected = [m, m, w - 2.0 * m, h - 2.0 * m];
+    assert!(page.clip.iter().zip(expected).all(|(a, b)| close(*a, b)));
+}
+
+#[test]
+fn margin_inside_the_bleed() {
+    for cover in [true, false] {
+        let page = page(&pdf::Options {
+            page: PageSize::A5,
+            margin: 10.0,
+            bleed: 3.0,
+            cover,
+            merge: false,
+        });
+        let [x0, y0, x1, y1] = page.trim.unwrap();
+        let b = 3.0 * POINTS_PER_MM;
+        assert!(close(x0, b) && close(y0, b));
+        assert!(close(page.media[2], x1 + b) && close(page.media[3], y1 + b));
+        // the full margin is left between the mesh and the trimmed edge
+        let m = 10.0 * POINTS_PER_MM;
+        let [x, y, w, h] = page.clip;
+        assert!(x >= x0 + m - 0.02 && y >= y0 + m - 0.02);
+        assert!(x + w <= x1 - m + 0.02 && y + h <= y1 - m + 0.02);
+        if cover {
+            assert!(close(x, x0 + m) && close(x + w, x1 - m));
+        }
+    }
+}
+
+#[test]
+fn no_margin_reaches_into_the_bleed() {
+    let page = page(&pdf::Options {
+        page: PageSize::A5,
+        margin: 0.0,
+        bleed: 3.0,
+        cover: true,
+        merge: false,
+    });
+    assert_eq!(page.clip, page.media);
+}
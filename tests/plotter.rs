@@ -0,0 +1,168 @@
+mod common;
+
+use common::mesh;
+use traingle::export::plotter;
+use traingle::Mesh;
+
+type Point = (f32, f32);
+
+struct Layer<'a> {
+    tag: &'a str,
+    // points of each path and whether it is closed
+    paths: Vec<(Vec<Point>, bool)>,
+}
+
+fn write(mesh: &Mesh, options: &plotter::Options) -> String {
+    let mut out = vec![];
+    plotter::write(mesh, options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    Some(&element[start..start + element[start..].find('"').unwrap()])
+}
+
+fn layers(svg: &str) -> Vec<Layer<'_>> {
+    svg.split("<g")
+        .skip(1)
+        .map(|group| {
+            let tag = &group[..group.find('>').unwrap()];
+            let paths = group
+                .split("<path d=\"")
+                .skip(1)
+                .map(|path| {
+                    let d = &path[..path.find('"').unwrap()];
+                    let points = d
+                        .trim_end_matches('Z')
+                        .split(['M', 'L'])
+                        .filter(|p| !p.is_empty())
+                        .map(|p| {
+                            let (x, y) = p.split_once(' ').unwrap();
+                            (x.parse().unwrap(), y.parse().unwrap())
+                        })
+                        .collect();
+                    (points, d.ends_with('Z'))
+                })
+                .collect();
+            Layer { tag, paths }
+        })
+        .collect()
+}
+
+fn luminance(hex: &str) -> f32 {
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap() as f32;
+    0.2126 * channel(1) + 0.7152 * channel(3) + 0.0722 * channel(5)
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+#[test]
+fn layers_by_color() {
+    let mesh = mesh();
+    let options = plotter::Options {
+        width: 100.0,
+        pen: 0.5,
+        ..plotter::Options::default()
+    };
+    let svg = write(&mesh, &options);
+    let root = &svg[..svg.find('>').unwrap()];
+    assert_eq!(attribute(root, "width"), Some("100mm"));
+    let height = format!("{}mm", 100.0 * mesh.height as f32 / mesh.width as f32);
+    assert_eq!(attribute(root, "height"), Some(height.as_str()));
+
+    let layers = layers(&svg);
+    let mut colors: Vec<String> = mesh
+        .colors
+        .iter()
+        .map(|[r, g, b]| format!("#{:02x}{:02x}{:02x}", r, g, b))
+        .collect();
+    colors.sort();
+    colors.dedup();
+    assert_eq!(layers.len(), colors.len());
+    let mut previous = f32::INFINITY;
+    for (n, layer) in layers.iter().enumerate() {
+        assert_eq!(attribute(layer.tag, "inkscape:groupmode"), Some("layer"));
+        let id = format!("layer{}", n + 1);
+        assert_eq!(attribute(layer.tag, "id"), Some(id.as_str()));
+        assert_eq!(attribute(layer.tag, "stroke-width"), Some("0.5"));
+        let color = attribute(layer.tag, "stroke").unwrap();
+        assert!(colors.iter().any(|c| c == color));
+        let label = format!("{} {}", n + 1, color);
+        assert_eq!(attribute(layer.tag, "inkscape:label"), Some(label.as_str()));
+        // lightest first
+        assert!(luminance(color) <= previous);
+        previous = luminance(color);
+        assert!(layer.paths.iter().all(|(_, closed)| *closed));
+    }
+}
+
+#[test]
+fn palette() {
+    let options = plotter::Options {
+        colors: Some(3),
+        ..plotter::Options::default()
+    };
+    let svg = write(&mesh(), &options);
+    let layers = layers(&svg);
+    assert!(!layers.is_empty() && layers.len() <= 3);
+}
+
+#[test]
+fn hatch() {
+    let options = plotter::Options {
+        hatch: Some(2.0),
+        hatch_angle: 30.0,
+        ..plotter::Options::default()
+    };
+    let svg = write(&mesh(), &options);
+    let layers = layers(&svg);
+    let (sin, cos) = 30f32.to_radians().sin_cos();
+    let mut lines = 0;
+    for layer in &layers {
+        for (points, closed) in &layer.paths {
+            if !closed {
+                let (a, b) = (points[0], points[1]);
+                // parallel to the hatch angle
+                let across = (b.1 - a.1) * cos - (b.0 - a.0) * sin;
+                assert!(across.abs() <= 0.02 * distance(a, b).max(1.0));
+                lines += 1;
+            }
+        }
+    }
+    assert!(lines > 0);
+}
+
+#[test]
+fn pen_order() {
+    let options = plotter::Options {
+        hatch: Some(3.0),
+        ..plotter::Options::default()
+    };
+    for layer in layers(&write(&mesh(), &options)) {
+        // every path starts where the pen is closest to, a closed one at any
+        // of its points and a line at either end
+        let mut pen = (0.0, 0.0);
+        for (i, (points, closed)) in layer.paths.iter().enumerate() {
+            let nearest = layer.paths[i..]
+                .iter()
+                .flat_map(|(points, closed)| {
+                    if *closed {
+                        points.clone()
+                    } else {
+                        vec![points[0], points[points.len() - 1]]
+                    }
+                })
+                .map(|start| distance(pen, start))
+                .fold(f32::INFINITY, f32::min);
+            assert!(distance(pen, points[0]) <= nearest + 0.02);
+            pen = if *closed {
+                points[0]
+            } else {
+                points[points.len() - 1]
+            };
+        }
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::mesh;
+use traingle::export::ply;
+
+#[test]
+fn faces() {
+    let mesh = mesh();
+    let mut out = vec![];
+    ply::write(&mesh, &ply::Options::default(), &mut out).unwrap();
+    let ply = String::from_utf8(out).unwrap();
+    let (header, body) = ply.split_once("end_header\n").unwrap();
+    assert!(header.starts_with("ply\nformat ascii 1.0\n"));
+    let vertex_count = format!("element vertex {}\n", mesh.points.len());
+    let face_count = format!("element face {}\n", mesh.triangles.len());
+    assert!(header.contains(&vertex_count) && header.contains(&face_count));
+
+    let lines: Vec<Vec<f32>> = body
+        .lines()
+        .map(|l| l.split(' ').map(|v| v.parse().unwrap()).collect())
+        .collect();
+    let (vertices, faces) = lines.split_at(mesh.points.len());
+    assert_eq!(faces.len(), mesh.triangles.len());
+    // y points up, so the image isn't mirrored
+    for (v, (x, y)) in vertices.iter().zip(&mesh.points) {
+        assert!((v[0] - x).abs() <= 0.005);
+        assert!((v[1] - (mesh.height as f32 - y)).abs() <= 0.005);
+        assert_eq!(v[2], 0.0);
+    }
+    for ((face, triangle), color) in faces.iter().zip(&mesh.triangles).zip(&mesh.colors) {
+        assert_eq!(face[0], 3.0);
+        let f: Vec<usize> = face[1..4].iter().map(|i| *i as usize).collect();
+        let mut sorted = f.clone();
+        sorted.sort_unstable();
+        let mut expected = *triangle;
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+        // counter-clockwise seen from +z
+        let (a, b, c) = (&vertices[f[0]], &vertices[f[1]], &vertices[f[2]]);
+        assert!((b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0]) >= 0.0);
+        let rgb: Vec<u8> = face[4..].iter().map(|c| *c as u8).collect();
+        assert_eq!(rgb, color);
+    }
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use common::mesh;
+use std::collections::{HashMap, HashSet};
+use std::convert::TryInto;
+use traingle::export::stl;
+use traingle::image::{GrayImage, Luma};
+
+fn triangles(data: &[u8]) -> Vec<[[f32; 3]; 3]> {
+    let count = u32::from_le_bytes(data[80..84].try_into().unwrap()) as usize;
+    assert_eq!(data.len(), 84 + count * 50);
+    let float = |at: usize| f32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    (0..count)
+        .map(|i| {
+            // skip the normal
+            let at = 84 + i * 50 + 12;
+            let vertex = |v: usize| {
+                [
+                    float(at + v * 12),
+                    float(at + v * 12 + 4),
+                    float(at + v * 12 + 8),
+                ]
+            };
+            [vertex(0), vertex(1), vertex(2)]
+        })
+        .collect()
+}
+
+// Panics unless the triangles bound a single closed solid, oriented outwards
+fn check_manifold(triangles: &[[[f32; 3]; 3]]) {
+    let mut ids: HashMap<[u32; 3], usize> = HashMap::new();
+    let mut faces = vec![];
+    for triangle in triangles {
+        let face: Vec<usize> = triangle
+            .iter()
+            .map(|v| {
+                let n = ids.len();
+                *ids.entry(v.map(f32::to_bits)).or_insert(n)
+            })
+            .collect();
+        assert!(
+            face[0] != face[1] && face[1] != face[2] && face[2] != face[0],
+            "degenerate triangle {:?}",
+            triangle
+        );
+        faces.push([face[0], face[1], face[2]]);
+    }
+
+    // every side is used once in each direction
+    let mut sides: HashMap<(usize, usize), u32> = HashMap::new();
+    for &[a, b, c] in &faces {
+        for side in [(a, b), (b, c), (c, a)] {
+            *sides.entry(side).or_default() += 1;
+        }
+    }
+    for (&(u, v), &n) in &sides {
+        assert_eq!(n, 1, "side {}-{} used {} times", u, v, n);
+        assert_eq!(sides.get(&(v, u)), Some(&1), "side {}-{} is open", u, v);
+    }
+
+    // the faces around every vertex form a single fan
+    let mut next: HashMap<usize, HashMap<usize, usize>> = HashMap::new();
+    for &[a, b, c] in &faces {
+        for (v, from, to) in [(a, b, c), (b, c, a), (c, a, b)] {
+            next.entry(v).or_default().insert(from, to);
+        }
+    }
+    for fan in next.values() {
+        let start = *fan.keys().next().unwrap();
+        let mut seen = HashSet::new();
+        let mut at = start;
+        while seen.insert(at) {
+            at = fan[&at];
+        }
+        assert_eq!(seen.len(), fan.len(), "vertex is not manifold");
+    }
+
+    // one piece without holes through it, a sphere
+    let vertices = ids.len() as i64;
+    let edges = sides.len() as i64 / 2;
+    assert_eq!(vertices - edges + faces.len() as i64, 2);
+
+    let volume: f32 = triangles
+        .iter()
+        .map(|[a, b, c]| {
+            a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                + a[2] * (b[0] * c[1] - b[1] * c[0])
+        })
+        .sum::<f32>()
+        / 6.0;
+    assert!(volume > 0.0, "solid is inside out, volume {}", volume);
+}
+
+#[test]
+fn luminance_relief_is_manifold() {
+    let mesh = mesh();
+    let mut data = vec![];
+    stl::write(&mesh, &stl::Options::default(), &mut data).unwrap();
+    let triangles = triangles(&data);
+    check_manifold(&triangles);
+    // top, base and two triangles per side on the outline
+    assert!(triangles.len() > 2 * mesh.triangles.len());
+    let max = triangles.iter().flatten().map(|v| v[2]).fold(0.0, f32::max);
+    assert!(max > 1.0 && max <= 4.0);
+}
+
+#[test]
+fn height_map_relief_is_manifold() {
+    let mesh = mesh();
+    let map = GrayImage::from_fn(16, 16, |x, y| Luma([((x * y) % 256) as u8]));
+    let options = stl::Options {
+        heights: stl::Heights::Map(map),
+        invert: true,
+        width: 50.0,
+        ..stl::Options::default()
+    };
+    let mut data = vec![];
+    stl::write(&mesh, &options, &mut data).unwrap();
+    check_manifold(&triangles(&data));
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use common::mesh;
+use traingle::export::svg;
+use traingle::Mesh;
+
+fn write(mesh: &Mesh, options: svg::Options) -> String {
+    let mut out = vec![];
+    svg::write(mesh, &options, &mut out).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+// Opening tags of the `tag` elements, in document order
+fn elements<'a>(svg: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{} ", tag);
+    svg.match_indices(&open)
+        .map(|(at, _)| &svg[at..at + svg[at..].find('>').unwrap()])
+        .collect()
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    Some(&element[start..start + element[start..].find('"').unwrap()])
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+// Signed area of every closed outline of a path, holes count against it
+fn area(d: &str) -> f32 {
+    let mut total = 0.0;
+    for outline in d.split('Z').filter(|o| !o.is_empty()) {
+        let points: Vec<(f32, f32)> = outline
+            .split(['M', 'L'])
+            .filter(|p| !p.is_empty())
+            .map(|p| {
+                let mut xy = p.split(' ').map(|v| v.parse::<f32>().unwrap());
+                (xy.next().unwrap(), xy.next().unwrap())
+            })
+            .collect();
+        let n = points.len();
+        total += (0..n)
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % n]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum::<f32>()
+            / 2.0;
+    }
+    total
+}
+
+fn total_area(mesh: &Mesh) -> f32 {
+    (0..mesh.triangles.len())
+        .map(|i| mesh.triangle_area(i))
+        .sum()
+}
+
+#[test]
+fn polygons() {
+    let mesh = mesh();
+    let svg = write(&mesh, svg::Options::default());
+    let root = elements(&svg, "svg")[0];
+    let view_box = format!("0 0 {} {}", mesh.width, mesh.height);
+    assert_eq!(attribute(root, "viewBox"), Some(view_box.as_str()));
+    let polygons = elements(&svg, "polygon");
+    assert_eq!(polygons.len(), mesh.triangles.len());
+    for ((polygon, triangle), color) in polygons.iter().zip(&mesh.triangles).zip(&mesh.colors) {
+        assert_eq!(attribute(polygon, "fill"), Some(hex(*color).as_str()));
+        let points: Vec<(f32, f32)> = attribute(polygon, "points")
+            .unwrap()
+            .split(' ')
+            .map(|p| {
+                let (x, y) = p.split_once(',').unwrap();
+                (x.parse().unwrap(), y.parse().unwrap())
+            })
+            .collect();
+        for (point, i) in points.iter().zip(triangle) {
+            let expected = mesh.points[*i];
+            assert!((point.0 - expected.0).abs() <= 0.005);
+            assert!((point.1 - expected.1).abs() <= 0.005);
+        }
+    }
+}
+
+#[test]
+fn group_by_color() {
+    let mesh = mesh();
+    let options = svg::Options {
+        group_by_color: true,
+        merge: false,
+    };
+    let svg = write(&mesh, options);
+    let mut colors: Vec<String> = mesh.colors.iter().map(|c| hex(*c)).collect();
+    colors.sort();
+    colors.dedup();
+    let groups: Vec<&str> = elements(&svg, "g")
+        .iter()
+        .map(|g| attribute(g, "fill").unwrap())
+        .collect();
+    assert_eq!(groups, colors);
+    // the group carries the fill
+    let polygons = elements(&svg, "polygon");
+    assert_eq!(polygons.len(), mesh.triangles.len());
+    assert!(polygons.iter().all(|p| attribute(p, "fill").is_none()));
+}
+
+#[test]
+fn merge() {
+    let mut mesh = mesh();
+    let options = svg::Options {
+        group_by_color: false,
+        merge: true,
+    };
+    // the paths cover the mesh once
+    let svg = write(&mesh, options);
+    let covered: f32 = elements(&svg, "path")
+        .iter()
+        .map(|p| area(attribute(p, "d").unwrap()).abs())
+        .sum();
+    assert!((covered - total_area(&mesh)).abs() < 1.0);
+
+    // a single color is a single path
+    let inner = (0..mesh.triangles.len())
+        .find(|i| {
+            mesh.triangles[*i].iter().all(|p| {
+                let (x, y) = mesh.points[*p];
+                x > 0.0 && y > 0.0 && x < mesh.width as f32 && y < mesh.height as f32
+            })
+        })
+        .unwrap();
+    for color in &mut mesh.colors {
+        *color = [10, 20, 30];
+    }
+    let svg = write(&mesh, options);
+    let paths = elements(&svg, "path");
+    assert_eq!(paths.len(), 1);
+    assert_eq!(attribute(paths[0], "d").unwrap().matches('Z').count(), 1);
+
+    // with a hole where a face inside it has another color
+    mesh.colors[inner] = [200, 100, 0];
+    let svg = write(&mesh, options);
+    let paths = elements(&svg, "path");
+    assert_eq!(paths.len(), 2);
+    let d = paths
+        .iter()
+        .map(|p| attribute(p, "d").unwrap())
+        .find(|d| d.matches('Z').count() == 2)
+        .unwrap();
+    let expected = total_area(&mesh) - mesh.triangle_area(inner);
+    assert!((area(d).abs() - expected).abs() < 1.0);
+}